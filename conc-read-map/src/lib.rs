@@ -99,7 +99,9 @@ where
     }
 }
 
-pub struct Call<'env, M>(SyncWrapper<Box<dyn 'env + FnMut(&mut M, Order) + Send>>);
+type BoxedCall<'env, M> = Box<dyn 'env + FnMut(&mut M, Order) + Send>;
+
+pub struct Call<'env, M>(SyncWrapper<BoxedCall<'env, M>>);
 
 #[repr(u8)]
 #[derive(Clone, Copy)]
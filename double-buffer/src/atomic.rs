@@ -1,8 +1,16 @@
-use core::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use core::task::{Context, Poll};
 
-use crate::Strategy;
+use crate::{
+    loom::atomic::{AtomicUsize, Ordering},
+    side::Side,
+    waker::AtomicWaker,
+    Strategy,
+};
 
-pub type BufferData<B, E = ()> = crate::BufferData<AtomicBool, AtomicStrategy, B, E>;
+pub mod sharded;
+pub use sharded::ShardedStrategy;
+
+pub type BufferData<B, E = ()> = crate::BufferData<AtomicUsize, AtomicStrategy, B, E>;
 
 crate::__imp_make_newtype! {
     crate::atomic::AtomicStrategy, core::convert::Infallible, ArcInner, std::sync::Arc
@@ -10,17 +18,19 @@ crate::__imp_make_newtype! {
 
 #[derive(Default)]
 pub struct AtomicStrategy {
-    num_readers: AtomicUsize,
+    side: Side,
+    num_readers: [AtomicUsize; 2],
+    waker: AtomicWaker,
 }
 
-pub struct RawGuard(());
-pub struct Capture(());
+pub struct RawGuard(usize);
+pub struct Capture(usize);
 
 pub struct ReaderTag(());
 pub struct WriterTag(());
 
 unsafe impl Strategy for AtomicStrategy {
-    type Whitch = AtomicBool;
+    type Whitch = AtomicUsize;
     type ReaderTag = ReaderTag;
     type WriterTag = WriterTag;
     type RawGuard = RawGuard;
@@ -29,55 +39,63 @@ unsafe impl Strategy for AtomicStrategy {
     type CaptureError = core::convert::Infallible;
     type Capture = Capture;
 
+    const READERS_EXIT_IN_END_GUARD: bool = true;
+
     #[inline]
     unsafe fn reader_tag(&self) -> Self::ReaderTag { ReaderTag(()) }
 
     #[inline]
     unsafe fn writer_tag(&self) -> Self::WriterTag { WriterTag(()) }
 
+    #[inline]
+    fn has_active_readers(&self, _: &mut Self::WriterTag) -> bool {
+        self.num_readers.iter().any(|num_readers| num_readers.load(Ordering::SeqCst) != 0)
+    }
+
     #[inline]
     fn try_capture_readers(&self, _: &mut Self::WriterTag) -> Result<Self::FastCapture, Self::CaptureError> { Ok(()) }
 
     #[inline]
     fn finish_capture_readers(&self, _: &mut Self::WriterTag, (): Self::FastCapture) -> Self::Capture {
-        use crossbeam_utils::Backoff;
-
-        let backoff = Backoff::new();
-
-        while self.num_readers.load(Ordering::Relaxed) != 0 {
-            backoff.snooze()
-        }
-
-        Capture(())
+        let side = self.side.flip();
+        self.num_readers[side].fetch_add(0, Ordering::SeqCst);
+        Capture(side)
     }
 
-    fn readers_have_exited(&self, _: &mut Self::Capture) -> bool { true }
-
-    fn begin_guard(&self, _: &mut Self::ReaderTag) -> Self::RawGuard {
-        #[cold]
-        #[inline(never)]
-        fn begin_guard_fail() -> ! {
-            struct Abort;
-
-            impl Drop for Abort {
-                fn drop(&mut self) { panic!() }
-            }
-
-            // double panic = abort
-            let _abort = Abort;
+    #[inline]
+    fn readers_have_exited(&self, &mut Capture(side): &mut Self::Capture) -> bool {
+        self.num_readers[side].load(Ordering::Acquire) == 0
+    }
 
-            panic!("Tried to create more than `isize::MAX` guards!")
+    fn poll_readers_have_exited(&self, capture: &mut Self::Capture, ctx: &mut Context<'_>) -> Poll<()> {
+        if self.readers_have_exited(capture) {
+            return Poll::Ready(())
         }
 
-        let num_readers = self.num_readers.fetch_add(1, Ordering::Acquire);
+        self.waker.register(ctx.waker());
 
-        if num_readers > isize::MAX as usize {
-            begin_guard_fail()
+        if self.num_readers[capture.0].load(Ordering::SeqCst) == 0 {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
         }
+    }
 
-        RawGuard(())
+    fn begin_guard(&self, _: &mut Self::ReaderTag) -> Self::RawGuard {
+        RawGuard(self.side.enter(
+            isize::MAX as usize,
+            |side| self.num_readers[side].fetch_add(1, Ordering::SeqCst),
+            |side| self.end_guard(RawGuard(side)),
+        ))
     }
 
     #[inline]
-    fn end_guard(&self, _: Self::RawGuard) { self.num_readers.fetch_sub(1, Ordering::Release); }
+    fn end_guard(&self, RawGuard(side): Self::RawGuard) {
+        if self.num_readers[side].fetch_sub(1, Ordering::SeqCst) == 1 && self.waker.is_registered() {
+            self.waker.wake()
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn captured_readers(&self, &Capture(side): &Self::Capture) -> usize { self.num_readers[side].load(Ordering::Relaxed) }
 }
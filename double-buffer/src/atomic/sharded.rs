@@ -0,0 +1,143 @@
+use core::task::{Context, Poll};
+use crossbeam_utils::CachePadded;
+
+use crate::{
+    loom::atomic::{AtomicUsize, Ordering},
+    side::Side,
+    waker::AtomicWaker,
+    Strategy,
+};
+
+pub type BufferData<B, E = ()> = crate::BufferData<AtomicUsize, ShardedStrategy, B, E>;
+
+crate::__imp_make_newtype! {
+    crate::atomic::sharded::ShardedStrategy, core::convert::Infallible, ArcInner, std::sync::Arc
+}
+
+// must fit in the bit mask kept by `Capture`
+const SHARDS: usize = 16;
+
+// like `AtomicStrategy`, but every shard counts the readers on each side
+#[derive(Default)]
+pub struct ShardedStrategy {
+    side: Side,
+    shards: [CachePadded<[AtomicUsize; 2]>; SHARDS],
+    next_shard: AtomicUsize,
+    waker: AtomicWaker,
+}
+
+pub struct RawGuard {
+    shard: usize,
+    side: usize,
+}
+
+pub struct Capture {
+    side: usize,
+    // shards which had active readers on the captured side when the
+    // buffers were swapped, and haven't been seen empty since
+    pending: usize,
+}
+
+pub struct ReaderTag(usize);
+pub struct WriterTag(());
+
+impl ShardedStrategy {
+    fn update_pending(&self, capture: &mut Capture) -> bool {
+        let mut pending = capture.pending;
+
+        while pending != 0 {
+            let shard = pending.trailing_zeros() as usize;
+            pending &= pending - 1;
+
+            if self.shards[shard][capture.side].load(Ordering::SeqCst) == 0 {
+                capture.pending &= !(1 << shard);
+            }
+        }
+
+        capture.pending == 0
+    }
+}
+
+unsafe impl Strategy for ShardedStrategy {
+    type Whitch = AtomicUsize;
+    type ReaderTag = ReaderTag;
+    type WriterTag = WriterTag;
+    type RawGuard = RawGuard;
+
+    type FastCapture = ();
+    type CaptureError = core::convert::Infallible;
+    type Capture = Capture;
+
+    const READERS_EXIT_IN_END_GUARD: bool = true;
+
+    #[inline]
+    unsafe fn reader_tag(&self) -> Self::ReaderTag {
+        ReaderTag(self.next_shard.fetch_add(1, Ordering::Relaxed) % SHARDS)
+    }
+
+    #[inline]
+    unsafe fn writer_tag(&self) -> Self::WriterTag { WriterTag(()) }
+
+    fn has_active_readers(&self, _: &mut Self::WriterTag) -> bool {
+        self.shards.iter().flat_map(|shard| shard.iter()).any(|side| side.load(Ordering::SeqCst) != 0)
+    }
+
+    #[inline]
+    fn try_capture_readers(&self, _: &mut Self::WriterTag) -> Result<Self::FastCapture, Self::CaptureError> { Ok(()) }
+
+    fn finish_capture_readers(&self, _: &mut Self::WriterTag, (): Self::FastCapture) -> Self::Capture {
+        let side = self.side.flip();
+        let pending = self
+            .shards
+            .iter()
+            .enumerate()
+            .filter(|(_, shard)| shard[side].fetch_add(0, Ordering::SeqCst) != 0)
+            .fold(0, |pending, (i, _)| pending | 1 << i);
+
+        Capture { side, pending }
+    }
+
+    #[inline]
+    fn readers_have_exited(&self, capture: &mut Self::Capture) -> bool {
+        capture.pending == 0 || self.update_pending(capture)
+    }
+
+    fn poll_readers_have_exited(&self, capture: &mut Self::Capture, ctx: &mut Context<'_>) -> Poll<()> {
+        if self.readers_have_exited(capture) {
+            return Poll::Ready(())
+        }
+
+        self.waker.register(ctx.waker());
+
+        if self.update_pending(capture) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    fn begin_guard(&self, &mut ReaderTag(shard): &mut Self::ReaderTag) -> Self::RawGuard {
+        let side = self.side.enter(
+            isize::MAX as usize,
+            |side| self.shards[shard][side].fetch_add(1, Ordering::SeqCst),
+            |side| self.end_guard(RawGuard { shard, side }),
+        );
+
+        RawGuard { shard, side }
+    }
+
+    #[inline]
+    fn end_guard(&self, RawGuard { shard, side }: Self::RawGuard) {
+        if self.shards[shard][side].fetch_sub(1, Ordering::SeqCst) == 1 && self.waker.is_registered() {
+            self.waker.wake()
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn captured_readers(&self, capture: &Self::Capture) -> usize {
+        (0..SHARDS)
+            .filter(|shard| capture.pending & (1 << shard) != 0)
+            .map(|shard| self.shards[shard][capture.side].load(Ordering::Relaxed))
+            .sum()
+    }
+}
@@ -1,23 +1,28 @@
-use super::{BufferData, BufferRef, BufferRefData, Strategy, TrustedRadium};
+use super::{BufferCount, BufferData, BufferRef, BufferRefData, Strategy};
 #[cfg(feature = "alloc")]
-use crate::thin::{Thin, ThinInner};
+use crate::{
+    thin::{Thin, ThinInner},
+    TrustedRadium,
+};
 use core::convert::Infallible;
 
 #[cfg(feature = "alloc")]
 use std::{
     boxed::Box,
-    rc::{self, Rc},
-    sync::{self, Arc},
+    rc::Rc,
+    sync::Arc,
 };
 
-unsafe impl<'a, B, S, E> BufferRef for &'a mut BufferData<S::Whitch, S, B, E>
+unsafe impl<'a, B, S, E, C> BufferRef for &'a mut BufferData<S::Whitch, S, B, E, C>
 where
+    C: BufferCount,
     S: Strategy,
     E: ?Sized,
 {
     type Buffer = B;
     type Strategy = S;
     type Extra = E;
+    type Count = C;
     type UpgradeError = Infallible;
 
     type Strong = &'a BufferRefData<Self>;
@@ -29,6 +34,8 @@ where
 
     fn upgrade(weak: &Self::Weak) -> Result<Self::Strong, Self::UpgradeError> { Ok(*weak) }
 
+    fn upgrade_ref(weak: &Self::Weak) -> Result<&BufferRefData<Self>, Self::UpgradeError> { Ok(*weak) }
+
     fn downgrade(strong: &Self::Strong) -> Self::Weak { *strong }
 }
 
@@ -37,53 +44,62 @@ where
 pub struct UpgradeFailed;
 
 #[cfg(feature = "alloc")]
-pub struct PinnedRcWeak<T: ?Sized>(rc::Weak<T>);
-#[cfg(feature = "alloc")]
-impl<T: ?Sized> Clone for PinnedRcWeak<T> {
-    fn clone(&self) -> Self { Self(self.0.clone()) }
-}
-
-#[cfg(feature = "alloc")]
-unsafe impl<B, S, E> BufferRef for Rc<BufferData<S::Whitch, S, B, E>>
+unsafe impl<B, S, E, C> BufferRef for Rc<BufferData<S::Whitch, S, B, E, C>>
 where
+    C: BufferCount,
     S: Strategy,
     E: ?Sized,
 {
     type Buffer = B;
     type Strategy = S;
     type Extra = E;
+    type Count = C;
     type UpgradeError = UpgradeFailed;
 
+    // readers keep the buffers alive, so that reading doesn't need to upgrade a weak pointer,
+    // and the writer is only considered alive until it is dropped
     type Strong = Rc<BufferRefData<Self>>;
-    type Weak = rc::Weak<BufferRefData<Self>>;
+    type Weak = Rc<BufferRefData<Self>>;
 
     fn split(mut self) -> (Self::Strong, Self::Weak) {
         assert!(Rc::get_mut(&mut self).is_some(), "Tried to split a shared `Rc`!");
-        let weak = Rc::downgrade(&self);
+        let weak = self.clone();
         (self, weak)
     }
 
-    fn is_dangling(weak: &Self::Weak) -> bool { rc::Weak::strong_count(&weak) == 0 }
+    fn is_dangling(weak: &Self::Weak) -> bool { weak.is_writer_dropped() }
 
-    fn upgrade(weak: &Self::Weak) -> Result<Self::Strong, Self::UpgradeError> { weak.upgrade().ok_or(UpgradeFailed) }
+    fn upgrade(weak: &Self::Weak) -> Result<Self::Strong, Self::UpgradeError> {
+        Self::upgrade_ref(weak).map(|_| weak.clone())
+    }
 
-    fn downgrade(strong: &Self::Strong) -> Self::Weak { Rc::downgrade(strong) }
+    fn upgrade_ref(weak: &Self::Weak) -> Result<&BufferRefData<Self>, Self::UpgradeError> {
+        if weak.is_writer_dropped() {
+            Err(UpgradeFailed)
+        } else {
+            Ok(weak)
+        }
+    }
+
+    fn downgrade(strong: &Self::Strong) -> Self::Weak { strong.clone() }
 }
 
 #[cfg(feature = "alloc")]
-unsafe impl<B, S, E, C> BufferRef for Box<ThinInner<BufferData<S::Whitch, S, B, E>, C>>
+unsafe impl<B, S, E, C, R> BufferRef for Box<ThinInner<BufferData<S::Whitch, S, B, E, C>, R>>
 where
-    C: TrustedRadium<Item = usize>,
+    C: BufferCount,
+    R: TrustedRadium<Item = usize>,
     S: Strategy,
     E: ?Sized,
 {
     type Buffer = B;
     type Strategy = S;
     type Extra = E;
+    type Count = C;
     type UpgradeError = Infallible;
 
-    type Strong = Thin<BufferRefData<Self>, C>;
-    type Weak = Thin<BufferRefData<Self>, C>;
+    type Strong = Thin<BufferRefData<Self>, R>;
+    type Weak = Thin<BufferRefData<Self>, R>;
 
     fn split(self) -> (Self::Strong, Self::Weak) {
         let this = Thin::from(self);
@@ -94,32 +110,46 @@ where
 
     fn upgrade(weak: &Self::Weak) -> Result<Self::Strong, Self::UpgradeError> { Ok(Thin::clone(weak)) }
 
+    fn upgrade_ref(weak: &Self::Weak) -> Result<&BufferRefData<Self>, Self::UpgradeError> { Ok(weak) }
+
     fn downgrade(strong: &Self::Strong) -> Self::Weak { strong.clone() }
 }
 
 #[cfg(feature = "alloc")]
-unsafe impl<B, S, E> BufferRef for Arc<BufferData<S::Whitch, S, B, E>>
+unsafe impl<B, S, E, C> BufferRef for Arc<BufferData<S::Whitch, S, B, E, C>>
 where
+    C: BufferCount,
     S: Strategy,
     E: ?Sized,
 {
     type Buffer = B;
     type Strategy = S;
     type Extra = E;
+    type Count = C;
     type UpgradeError = UpgradeFailed;
 
     type Strong = Arc<BufferRefData<Self>>;
-    type Weak = sync::Weak<BufferRefData<Self>>;
+    type Weak = Arc<BufferRefData<Self>>;
 
     fn split(mut self) -> (Self::Strong, Self::Weak) {
         assert!(Arc::get_mut(&mut self).is_some(), "Tried to split a shared `Arc`!");
-        let weak = Arc::downgrade(&self);
+        let weak = self.clone();
         (self, weak)
     }
 
-    fn is_dangling(weak: &Self::Weak) -> bool { sync::Weak::strong_count(&weak) == 0 }
+    fn is_dangling(weak: &Self::Weak) -> bool { weak.is_writer_dropped() }
 
-    fn upgrade(weak: &Self::Weak) -> Result<Self::Strong, Self::UpgradeError> { weak.upgrade().ok_or(UpgradeFailed) }
+    fn upgrade(weak: &Self::Weak) -> Result<Self::Strong, Self::UpgradeError> {
+        Self::upgrade_ref(weak).map(|_| weak.clone())
+    }
 
-    fn downgrade(strong: &Self::Strong) -> Self::Weak { Arc::downgrade(strong) }
+    fn upgrade_ref(weak: &Self::Weak) -> Result<&BufferRefData<Self>, Self::UpgradeError> {
+        if weak.is_writer_dropped() {
+            Err(UpgradeFailed)
+        } else {
+            Ok(weak)
+        }
+    }
+
+    fn downgrade(strong: &Self::Strong) -> Self::Weak { strong.clone() }
 }
@@ -0,0 +1,49 @@
+use std::{
+    sync::{Arc, Mutex, PoisonError},
+    vec::Vec,
+};
+
+// the live guards created by a thread, as the address of their buffer data and the index of their buffer.
+// Guards may be dropped on another thread, so they keep the list of the thread that created them
+pub(crate) type Guards = Arc<Mutex<Vec<(usize, usize)>>>;
+
+std::thread_local! {
+    static GUARDS: Guards = Guards::default();
+}
+
+pub(crate) fn acquire(data: usize, index: usize) -> Guards {
+    GUARDS.with(|guards| {
+        guards
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .push((data, index));
+        guards.clone()
+    })
+}
+
+pub(crate) fn release(guards: &Guards, data: usize, index: usize) {
+    let mut guards = guards.lock().unwrap_or_else(PoisonError::into_inner);
+
+    if let Some(position) = guards.iter().position(|&guard| guard == (data, index)) {
+        guards.swap_remove(position);
+    }
+}
+
+// panics if the current thread holds a guard to a buffer that the swap is waiting on,
+// since the swap could never finish
+pub(crate) fn check(data: usize, is_waiting_on: impl Fn(usize) -> bool) {
+    let is_deadlocked = GUARDS.with(|guards| {
+        guards
+            .lock()
+            .unwrap_or_else(PoisonError::into_inner)
+            .iter()
+            .any(|&(guard_data, index)| guard_data == data && is_waiting_on(index))
+    });
+
+    if is_deadlocked {
+        panic!(
+            "Tried to wait for a swap to finish while this thread holds a `ReaderGuard` to the old read buffer, \
+             this would deadlock. Drop the guard before swapping buffers."
+        )
+    }
+}
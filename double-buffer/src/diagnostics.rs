@@ -0,0 +1,71 @@
+use core::{fmt, panic::Location};
+use std::borrow::Cow;
+
+#[derive(Clone, Debug)]
+pub struct ReaderInfo {
+    name: Option<Cow<'static, str>>,
+    location: &'static Location<'static>,
+    #[cfg(feature = "std")]
+    backtrace: Option<std::sync::Arc<std::backtrace::Backtrace>>,
+}
+
+impl ReaderInfo {
+    #[track_caller]
+    pub fn new() -> Self {
+        let info = Self::with_location(Location::caller());
+        #[cfg(feature = "std")]
+        let info = info.with_backtrace();
+        info
+    }
+
+    pub fn with_location(location: &'static Location<'static>) -> Self {
+        Self {
+            name: None,
+            location,
+            #[cfg(feature = "std")]
+            backtrace: None,
+        }
+    }
+
+    // only captures a backtrace if they are enabled through `RUST_BACKTRACE` or `RUST_LIB_BACKTRACE`
+    #[cfg(feature = "std")]
+    pub fn with_backtrace(mut self) -> Self {
+        let backtrace = std::backtrace::Backtrace::capture();
+
+        if let std::backtrace::BacktraceStatus::Captured = backtrace.status() {
+            self.backtrace = Some(std::sync::Arc::new(backtrace))
+        }
+
+        self
+    }
+
+    pub fn name(&self) -> Option<&str> { self.name.as_deref() }
+
+    pub fn set_name(&mut self, name: Cow<'static, str>) { self.name = Some(name) }
+
+    pub fn location(&self) -> &'static Location<'static> { self.location }
+
+    #[cfg(feature = "std")]
+    pub fn backtrace(&self) -> Option<&std::backtrace::Backtrace> { self.backtrace.as_deref() }
+}
+
+impl Default for ReaderInfo {
+    #[track_caller]
+    fn default() -> Self { Self::new() }
+}
+
+impl fmt::Display for ReaderInfo {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.name {
+            Some(ref name) => write!(f, "reader `{}` created at {}", name, self.location)?,
+            None => write!(f, "reader created at {}", self.location)?,
+        }
+
+        #[cfg(feature = "std")]
+        if let Some(backtrace) = self.backtrace() {
+            write!(f, "\n{}", backtrace)?
+        }
+
+        Ok(())
+    }
+}
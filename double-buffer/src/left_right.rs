@@ -17,8 +17,10 @@ pub struct WriterRef<'a, O> {
 
 pub trait LeftRightStrategy: crate::Seal {}
 impl crate::Seal for crate::sync::SyncStrategy {}
+#[cfg(feature = "std")]
 impl crate::Seal for crate::sync::park::ParkStrategy {}
 impl LeftRightStrategy for crate::sync::SyncStrategy {}
+#[cfg(feature = "std")]
 impl LeftRightStrategy for crate::sync::park::ParkStrategy {}
 
 impl<B: BufferRef, O> From<crate::raw::Writer<B>> for Writer<B, O>
@@ -97,7 +99,7 @@ impl<O> WriterRef<'_, O> {
     pub fn register(&mut self, op: O) { self.ops.push_back(op); }
 
     #[inline]
-    pub fn operations(&self) -> &VecDeque<O> { &self.ops }
+    pub fn operations(&self) -> &VecDeque<O> { self.ops }
 }
 
 struct Counter(i64);
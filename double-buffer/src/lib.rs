@@ -1,3 +1,10 @@
+//! Readers of an `Rc` or `Arc` backed buffer hold a strong reference, so reading never has to
+//! upgrade a weak pointer. The buffers are only freed once the writer and every reader are
+//! dropped, and readers notice that the writer is gone through `Reader::is_dangling`.
+//!
+//! Since 0.2, `BufferRef` implementations also have to name their `Count` and provide
+//! `upgrade_ref`, which hands out the buffers without cloning the strong reference.
+
 #![cfg_attr(not(feature = "std"), no_std)]
 
 #[cfg(all(feature = "alloc", not(feature = "std")))]
@@ -9,6 +16,15 @@ mod macros;
 #[cfg(feature = "alloc")]
 pub mod op;
 
+#[cfg(feature = "alloc")]
+pub mod diagnostics;
+
+#[cfg(feature = "metrics")]
+pub mod metrics;
+
+#[cfg(all(feature = "testing", not(loom)))]
+pub mod testing;
+
 #[cfg(feature = "alloc")]
 pub mod left_right;
 
@@ -16,19 +32,41 @@ pub mod left_right;
 pub mod thin;
 
 pub mod raw;
+pub mod wait;
 pub use raw::{new, BufferData, Reader, Writer};
 
 pub mod atomic;
 pub mod local;
 #[cfg(feature = "alloc")]
 pub mod sync;
+#[cfg(feature = "std")]
+pub mod park;
+#[cfg(feature = "alloc")]
+pub mod qsbr;
+#[cfg(feature = "alloc")]
+pub mod ring;
+#[cfg(feature = "alloc")]
+pub mod triple;
 
-#[cfg(test)]
+#[cfg(all(test, not(loom)))]
 mod tests;
 
 mod buffer_ref;
+#[cfg(feature = "detect-deadlock")]
+mod deadlock;
+mod loom;
+#[cfg(feature = "alloc")]
+mod notify;
+mod side;
+mod waker;
 
-use core::ops::Deref;
+use core::{
+    ops::Deref,
+    sync::atomic::Ordering,
+    task::{Context, Poll},
+};
+
+#[cfg(not(loom))]
 use radium::Radium;
 
 use seal::Seal;
@@ -38,51 +76,174 @@ mod seal {
     pub trait Seal {}
 }
 
-pub unsafe trait TrustedRadium: Radium + Seal {
+/// # Safety
+///
+/// The operations must behave like the atomic operations they are named after, and
+/// `IS_LOCAL` must only be set by types that can't be shared between threads.
+#[cfg(not(loom))]
+pub unsafe trait TrustedRadium: radium::Radium + Seal {
     #[doc(hidden)]
     const IS_LOCAL: bool;
+
     #[doc(hidden)]
     unsafe fn load_unsync(&self) -> Self::Item;
 }
 
-impl Seal for core::cell::Cell<bool> {}
-unsafe impl TrustedRadium for core::cell::Cell<bool> {
+// loom's atomics can't hand out a `&mut` to their value, so they can't implement `Radium`.
+// Under loom the trait spells out the parts of `Radium` that are used instead
+/// # Safety
+///
+/// The operations must behave like the atomic operations they are named after, and
+/// `IS_LOCAL` must only be set by types that can't be shared between threads.
+#[cfg(loom)]
+pub unsafe trait TrustedRadium: Seal {
+    type Item;
+
     #[doc(hidden)]
-    const IS_LOCAL: bool = true;
+    const IS_LOCAL: bool;
+
+    fn new(value: Self::Item) -> Self;
+
+    fn load(&self, order: Ordering) -> Self::Item;
+
+    fn store(&self, value: Self::Item, order: Ordering);
+
+    fn fetch_add(&self, value: Self::Item, order: Ordering) -> Self::Item;
+
+    fn fetch_sub(&self, value: Self::Item, order: Ordering) -> Self::Item;
+
+    fn compare_exchange(
+        &self,
+        current: Self::Item,
+        new: Self::Item,
+        success: Ordering,
+        failure: Ordering,
+    ) -> Result<Self::Item, Self::Item>;
+
     #[doc(hidden)]
-    unsafe fn load_unsync(&self) -> Self::Item { self.get() }
+    unsafe fn load_unsync(&self) -> Self::Item;
 }
 
-impl Seal for core::sync::atomic::AtomicBool {}
-unsafe impl TrustedRadium for core::sync::atomic::AtomicBool {
-    #[doc(hidden)]
-    const IS_LOCAL: bool = false;
-    #[doc(hidden)]
-    unsafe fn load_unsync(&self) -> Self::Item {
-        core::ptr::read(self as *const core::sync::atomic::AtomicBool as *const bool)
-    }
+#[cfg(not(loom))]
+macro_rules! trusted_radium {
+    ($type:ty, $is_local:literal, |$this:ident| $load_unsync:expr) => {
+        impl Seal for $type {}
+        unsafe impl TrustedRadium for $type {
+            #[doc(hidden)]
+            const IS_LOCAL: bool = $is_local;
+
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn load_unsync(&self) -> Self::Item {
+                let $this = self;
+                $load_unsync
+            }
+        }
+    };
 }
 
-impl Seal for core::cell::Cell<usize> {}
-unsafe impl TrustedRadium for core::cell::Cell<usize> {
-    #[doc(hidden)]
-    const IS_LOCAL: bool = true;
-    #[doc(hidden)]
-    unsafe fn load_unsync(&self) -> Self::Item { self.get() }
+#[cfg(loom)]
+macro_rules! trusted_radium {
+    ($type:ty, $is_local:literal, |$this:ident| $load_unsync:expr) => {
+        impl Seal for $type {}
+        unsafe impl TrustedRadium for $type {
+            type Item = usize;
+
+            #[doc(hidden)]
+            const IS_LOCAL: bool = $is_local;
+
+            #[inline]
+            fn new(value: usize) -> Self { radium::Radium::new(value) }
+
+            #[inline]
+            fn load(&self, order: Ordering) -> usize { radium::Radium::load(self, order) }
+
+            #[inline]
+            fn store(&self, value: usize, order: Ordering) { radium::Radium::store(self, value, order) }
+
+            #[inline]
+            fn fetch_add(&self, value: usize, order: Ordering) -> usize { radium::Radium::fetch_add(self, value, order) }
+
+            #[inline]
+            fn fetch_sub(&self, value: usize, order: Ordering) -> usize { radium::Radium::fetch_sub(self, value, order) }
+
+            #[inline]
+            fn compare_exchange(&self, current: usize, new: usize, success: Ordering, failure: Ordering) -> Result<usize, usize> {
+                radium::Radium::compare_exchange(self, current, new, success, failure)
+            }
+
+            #[doc(hidden)]
+            #[inline]
+            unsafe fn load_unsync(&self) -> usize {
+                let $this = self;
+                $load_unsync
+            }
+        }
+    };
 }
 
-impl Seal for core::sync::atomic::AtomicUsize {}
-unsafe impl TrustedRadium for core::sync::atomic::AtomicUsize {
+#[cfg(not(loom))]
+trusted_radium!(core::cell::Cell<bool>, true, |this| this.get());
+#[cfg(not(loom))]
+trusted_radium!(core::sync::atomic::AtomicBool, false, |this| core::ptr::read(
+    this as *const core::sync::atomic::AtomicBool as *const bool
+));
+trusted_radium!(core::cell::Cell<usize>, true, |this| this.get());
+trusted_radium!(core::sync::atomic::AtomicUsize, false, |this| core::ptr::read(
+    this as *const core::sync::atomic::AtomicUsize as *const usize
+));
+
+#[cfg(loom)]
+impl Seal for crate::loom::atomic::AtomicUsize {}
+#[cfg(loom)]
+unsafe impl TrustedRadium for crate::loom::atomic::AtomicUsize {
+    type Item = usize;
+
     #[doc(hidden)]
     const IS_LOCAL: bool = false;
-    #[doc(hidden)]
-    unsafe fn load_unsync(&self) -> Self::Item {
-        core::ptr::read(self as *const core::sync::atomic::AtomicUsize as *const usize)
+
+    fn new(value: usize) -> Self { Self::new(value) }
+
+    fn load(&self, order: Ordering) -> usize { self.load(order) }
+
+    fn store(&self, value: usize, order: Ordering) { self.store(value, order) }
+
+    fn fetch_add(&self, value: usize, order: Ordering) -> usize { self.fetch_add(value, order) }
+
+    fn fetch_sub(&self, value: usize, order: Ordering) -> usize { self.fetch_sub(value, order) }
+
+    fn compare_exchange(&self, current: usize, new: usize, success: Ordering, failure: Ordering) -> Result<usize, usize> {
+        self.compare_exchange(current, new, success, failure)
     }
+
+    #[doc(hidden)]
+    unsafe fn load_unsync(&self) -> usize { self.unsync_load() }
+}
+
+// the number of buffers as a type, so that `BufferRef` can name it
+/// # Safety
+///
+/// `Array<T>` must be `[T; N]`.
+pub unsafe trait BufferCount: Seal {
+    const N: usize;
+    type Array<T>: AsRef<[T]> + AsMut<[T]>;
+}
+
+pub struct Count<const N: usize>;
+
+impl<const N: usize> Seal for Count<N> {}
+unsafe impl<const N: usize> BufferCount for Count<N> {
+    const N: usize = N;
+    type Array<T> = [T; N];
 }
 
-pub type BufferRefData<BR> =
-    BufferData<Whitch<BR>, <BR as BufferRef>::Strategy, <BR as BufferRef>::Buffer, <BR as BufferRef>::Extra>;
+pub type BufferRefData<BR> = BufferData<
+    Whitch<BR>,
+    <BR as BufferRef>::Strategy,
+    <BR as BufferRef>::Buffer,
+    <BR as BufferRef>::Extra,
+    <BR as BufferRef>::Count,
+>;
 
 type Whitch<BR> = <<BR as BufferRef>::Strategy as Strategy>::Whitch;
 type ReaderTag<BR> = <<BR as BufferRef>::Strategy as Strategy>::ReaderTag;
@@ -90,10 +251,15 @@ type WriterTag<BR> = <<BR as BufferRef>::Strategy as Strategy>::WriterTag;
 type CaptureError<BR> = <<BR as BufferRef>::Strategy as Strategy>::CaptureError;
 type Capture<BR> = <<BR as BufferRef>::Strategy as Strategy>::Capture;
 
+/// # Safety
+///
+/// `split` and `downgrade` must return handles to the same buffers, and `upgrade_ref` may only
+/// hand out a reference if the buffers stay alive for as long as the weak handle does.
 pub unsafe trait BufferRef: Sized {
     type Buffer;
     type Strategy: Strategy;
     type Extra: ?Sized;
+    type Count: BufferCount;
     type UpgradeError: core::fmt::Debug;
 
     type Strong: Clone + Deref<Target = BufferRefData<Self>>;
@@ -105,11 +271,17 @@ pub unsafe trait BufferRef: Sized {
 
     fn upgrade(weak: &Self::Weak) -> Result<Self::Strong, Self::UpgradeError>;
 
+    fn upgrade_ref(weak: &Self::Weak) -> Result<&BufferRefData<Self>, Self::UpgradeError>;
+
     fn downgrade(strong: &Self::Strong) -> Self::Weak;
 }
 
+/// # Safety
+///
+/// A capture may only report that readers have exited once every reader that began a guard
+/// before the swap, and may be reading the buffer that the writer moves into, has ended it.
 pub unsafe trait Strategy: Sized {
-    type Whitch: TrustedRadium<Item = bool>;
+    type Whitch: TrustedRadium<Item = usize>;
     type ReaderTag;
     type WriterTag;
     type RawGuard;
@@ -118,20 +290,99 @@ pub unsafe trait Strategy: Sized {
     type CaptureError: core::fmt::Debug;
     type Capture;
 
+    // strategies that count readers per buffer only work with as many buffers as they count
+    const BUFFER_COUNT: Option<usize> = None;
+
+    // set by strategies whose readers only exit in `end_guard`, through a SeqCst read-modify-write.
+    // Wrappers like `park::Park` can then rely on `end_guard` alone to find out about exiting readers
+    const READERS_EXIT_IN_END_GUARD: bool = false;
+
+    /// # Safety
+    ///
+    /// The tag may only be used with this strategy, and must be dropped before it.
     unsafe fn reader_tag(&self) -> Self::ReaderTag;
 
+    /// # Safety
+    ///
+    /// There may only be one writer tag for this strategy at a time.
     unsafe fn writer_tag(&self) -> Self::WriterTag;
 
+    // strategies that can't tell conservatively report that readers may be active
+    #[inline]
+    fn has_active_readers(&self, _: &mut Self::WriterTag) -> bool { true }
+
     fn try_capture_readers(&self, tag: &mut Self::WriterTag) -> Result<Self::FastCapture, Self::CaptureError>;
 
     fn finish_capture_readers(&self, _: &mut Self::WriterTag, capture: Self::FastCapture) -> Self::Capture;
 
     fn readers_have_exited(&self, capture: &mut Self::Capture) -> bool;
 
+    // the default can't be woken up by readers, so it busy-waits: it wakes the task right away
+    // and the executor keeps polling it until the readers have exited. Strategies that support
+    // async swaps should register the waker and wake it once the last captured reader exits
+    fn poll_readers_have_exited(&self, capture: &mut Self::Capture, ctx: &mut Context<'_>) -> Poll<()> {
+        if self.readers_have_exited(capture) {
+            Poll::Ready(())
+        } else {
+            ctx.waker().wake_by_ref();
+            Poll::Pending
+        }
+    }
+
+    // block until some reader that the capture is waiting on may have exited, or until the timeout
+    // elapses. Strategies that can't block leave the writer to spin
+    fn park_writer(&self, _: &mut Self::Capture, _: Option<core::time::Duration>) {}
+
     fn begin_guard(&self, tag: &mut Self::ReaderTag) -> Self::RawGuard;
 
     fn end_guard(&self, guard: Self::RawGuard);
 
+    // begins a guard on the latest buffer and returns its version. Strategies that count readers
+    // per buffer have to know the version before they can register the reader, so they override this
+    #[inline]
+    fn begin_guard_latest(&self, tag: &mut Self::ReaderTag, which: &Self::Whitch) -> (Self::RawGuard, usize) {
+        let guard = self.begin_guard(tag);
+        (guard, which.load(Ordering::Acquire))
+    }
+
+    // begins a guard on the buffer that holds `version` and returns the latest version, which tells
+    // the caller whether that buffer still holds it. Strategies that pick the buffers themselves
+    // return `None` once no buffer holds the version anymore
+    #[inline]
+    fn begin_guard_at(&self, tag: &mut Self::ReaderTag, which: &Self::Whitch, _: usize) -> Option<(Self::RawGuard, usize)> {
+        Some(self.begin_guard_latest(tag, which))
+    }
+
+    // strategies that pick the buffers themselves keep track of the buffer the writer writes to, the
+    // buffer it published last and the buffer each guard reads. The others keep version `v` in buffer
+    // `v % N`, and the writer writes to the next one
+    #[inline]
+    fn write_index(&self, _: &Self::WriterTag) -> Option<usize> { None }
+
+    #[inline]
+    fn published_index(&self, _: &Self::WriterTag) -> Option<usize> { None }
+
+    #[inline]
+    fn guard_index(&self, _: &Self::RawGuard) -> Option<usize> { None }
+
+    // capturing the info can be expensive, so it's only done by strategies that keep it
+    #[cfg(feature = "alloc")]
+    fn describe_reader<F: FnOnce() -> diagnostics::ReaderInfo>(&self, _: &mut Self::ReaderTag, _: F) {}
+
+    #[cfg(feature = "alloc")]
+    fn name_reader(&self, _: &mut Self::ReaderTag, _: std::borrow::Cow<'static, str>) {}
+
+    // strategies that can't identify their readers report none
+    #[cfg(feature = "alloc")]
+    fn active_readers(&self) -> std::vec::Vec<diagnostics::ReaderInfo> { std::vec::Vec::new() }
+
+    #[cfg(feature = "alloc")]
+    fn blocking_readers(&self, _: &Self::Capture) -> std::vec::Vec<diagnostics::ReaderInfo> { std::vec::Vec::new() }
+
+    // the number of readers the swap has to wait for, right after the capture
+    #[cfg(feature = "metrics")]
+    fn captured_readers(&self, _: &Self::Capture) -> usize { 0 }
+
     fn is_swap_completed<B: BufferRef<Strategy = Self>>(&self, swap: &mut raw::Swap<B>) -> bool {
         raw::is_swap_completed(self, swap)
     }
@@ -2,7 +2,7 @@ use core::cell::Cell;
 
 use crate::Strategy;
 
-pub type BufferData<B, E = ()> = crate::BufferData<core::cell::Cell<bool>, LocalStrategy, B, E>;
+pub type BufferData<B, E = ()> = crate::BufferData<core::cell::Cell<usize>, LocalStrategy, B, E>;
 
 crate::__imp_make_newtype! {
     crate::local::LocalStrategy, crate::local::CaptureError, RcInner, std::rc::Rc
@@ -32,25 +32,13 @@ impl core::fmt::Debug for CaptureError {
 fn begin_guard_fail<T>() -> T { panic!("Tried to create too many readers!") }
 
 impl LocalStrategy {
-    pub fn try_swap_buffers<B: crate::BufferRef<Strategy = Self>>(writer: &mut crate::raw::Writer<B>) -> bool {
-        use crate::raw::Writer;
-
-        let strategy: &Self = Writer::strategy(writer);
-
-        let can_swap = strategy.num_readers.get() == 0;
-
-        if can_swap {
-            unsafe {
-                Writer::swap_buffers_unchecked(writer);
-            }
-        }
-
-        can_swap
+    pub fn try_swap_buffers<B: crate::BufferRef<Strategy = Self>>(writer: &mut crate::raw::Writer<B>) -> crate::raw::TrySwap {
+        crate::raw::Writer::try_swap_buffers(writer)
     }
 }
 
 unsafe impl Strategy for LocalStrategy {
-    type Whitch = core::cell::Cell<bool>;
+    type Whitch = core::cell::Cell<usize>;
     type ReaderTag = ReaderTag;
     type WriterTag = WriterTag;
     type RawGuard = RawGuard;
@@ -65,6 +53,9 @@ unsafe impl Strategy for LocalStrategy {
     #[inline]
     unsafe fn writer_tag(&self) -> Self::WriterTag { WriterTag(()) }
 
+    #[inline]
+    fn has_active_readers(&self, _: &mut Self::WriterTag) -> bool { self.num_readers.get() != 0 }
+
     #[inline]
     fn try_capture_readers(&self, _: &mut Self::WriterTag) -> Result<Self::FastCapture, Self::CaptureError> {
         if self.num_readers.get() == 0 {
@@ -0,0 +1,92 @@
+// all atomics, mutexes and condvars are used through this module, so that
+// building with `RUSTFLAGS="--cfg loom"` lets loom model check the strategies
+
+#[cfg(all(test, loom))]
+mod tests;
+
+#[cfg(not(loom))]
+pub(crate) mod atomic {
+    #[allow(unused_imports)]
+    pub(crate) use core::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+}
+
+#[cfg(loom)]
+pub(crate) mod atomic {
+    #[allow(unused_imports)]
+    pub(crate) use loom::sync::atomic::{fence, AtomicBool, AtomicPtr, AtomicU32, AtomicU64, AtomicUsize, Ordering};
+}
+
+#[cfg(all(feature = "std", not(loom)))]
+pub(crate) use parking_lot::{Condvar, Mutex};
+#[cfg(all(not(feature = "std"), not(loom)))]
+pub(crate) use spin::Mutex;
+
+#[cfg(loom)]
+pub(crate) use self::mutex::{Condvar, Mutex};
+
+// loom's `Mutex` and `Condvar` follow `std`, so these adapt them to `parking_lot`'s api
+#[cfg(loom)]
+mod mutex {
+    use core::ops::{Deref, DerefMut};
+
+    #[derive(Debug, Default)]
+    pub(crate) struct Mutex<T>(loom::sync::Mutex<T>);
+
+    pub(crate) struct MutexGuard<'a, T>(Option<loom::sync::MutexGuard<'a, T>>);
+
+    #[derive(Debug, Default)]
+    pub(crate) struct Condvar(loom::sync::Condvar);
+
+    pub(crate) struct WaitTimeoutResult(());
+
+    impl<T> Mutex<T> {
+        pub fn new(value: T) -> Self { Self(loom::sync::Mutex::new(value)) }
+
+        pub fn lock(&self) -> MutexGuard<'_, T> { MutexGuard(Some(self.0.lock().unwrap())) }
+    }
+
+    impl<T> Deref for MutexGuard<'_, T> {
+        type Target = T;
+
+        fn deref(&self) -> &T { self.0.as_ref().unwrap() }
+    }
+
+    impl<T> DerefMut for MutexGuard<'_, T> {
+        fn deref_mut(&mut self) -> &mut T { self.0.as_mut().unwrap() }
+    }
+
+    // loom never times out a wait, it only explores the executions where the waiter gets notified
+    impl Condvar {
+        pub fn new() -> Self { Self(loom::sync::Condvar::new()) }
+
+        pub fn wait<T>(&self, guard: &mut MutexGuard<'_, T>) {
+            let inner = guard.0.take().unwrap();
+            guard.0 = Some(self.0.wait(inner).unwrap());
+        }
+
+        pub fn wait_for<T>(&self, guard: &mut MutexGuard<'_, T>, _: core::time::Duration) -> WaitTimeoutResult {
+            self.wait(guard);
+            WaitTimeoutResult(())
+        }
+
+        pub fn wait_until<T>(&self, guard: &mut MutexGuard<'_, T>, _: std::time::Instant) -> WaitTimeoutResult {
+            self.wait(guard);
+            WaitTimeoutResult(())
+        }
+
+        pub fn notify_one(&self) { self.0.notify_one(); }
+
+        pub fn notify_all(&self) { self.0.notify_all(); }
+    }
+
+    impl WaitTimeoutResult {
+        pub fn timed_out(&self) -> bool { false }
+    }
+}
+
+// a spinning writer has to let loom run the other threads, otherwise loom would explore it spinning forever
+#[inline]
+pub(crate) fn spin_wait() {
+    #[cfg(loom)]
+    loom::thread::yield_now();
+}
@@ -0,0 +1,195 @@
+// run with `RUSTFLAGS="--cfg loom" cargo test -p double-buffer --release --lib loom`
+
+use loom::{cell::UnsafeCell, thread};
+use std::sync::Arc;
+
+use crate::{Count, Strategy, Writer};
+
+type BufferData<S, const N: usize> = crate::BufferData<<S as Strategy>::Whitch, S, Buffer, (), Count<N>>;
+
+// loom reports a causality violation if the writer writes to a buffer while a reader is still reading it
+#[derive(Default)]
+struct Buffer(UnsafeCell<usize>);
+
+unsafe impl Sync for Buffer {}
+
+impl Buffer {
+    fn read(&self) -> usize { self.0.with(|value| unsafe { *value }) }
+
+    fn write(&self, value: usize) { self.0.with_mut(|slot| unsafe { *slot = value }) }
+}
+
+fn swap_while_reading<S, const N: usize>()
+where
+    S: Strategy + Default + Send + Sync + 'static,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    loom::model(|| {
+        let buffer_data = Arc::new(BufferData::<S, N>::default());
+        let (mut r, mut w) = crate::new(buffer_data);
+
+        let reader = thread::spawn(move || {
+            let value = r.get().read();
+            assert!(value == 0 || value == 1);
+        });
+
+        w.write(1);
+        Writer::swap_buffers(&mut w);
+        w.write(2);
+
+        reader.join().unwrap();
+    });
+}
+
+fn register_while_swapping<S, const N: usize>()
+where
+    S: Strategy + Default + Send + Sync + 'static,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    loom::model(|| {
+        let buffer_data = Arc::new(BufferData::<S, N>::default());
+        let (r, mut w) = crate::new(buffer_data);
+
+        let reader = thread::spawn(move || {
+            let mut r = r.try_clone().unwrap();
+            let _ = r.get().read();
+        });
+
+        Writer::swap_buffers(&mut w);
+        w.write(1);
+
+        reader.join().unwrap();
+    });
+}
+
+// the readers of a swap that was left pending must not be forgotten by the next swap
+fn swap_after_pending_swap<S, const N: usize>()
+where
+    S: Strategy + Default + Send + Sync + 'static,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    loom::model(|| {
+        let buffer_data = Arc::new(BufferData::<S, N>::default());
+        let (mut r, mut w) = crate::new(buffer_data);
+
+        let reader = thread::spawn(move || {
+            let _ = r.get().read();
+        });
+
+        w.write(1);
+        drop(Writer::swap_buffers_async(&mut w));
+        Writer::swap_buffers(&mut w);
+        w.write(2);
+
+        reader.join().unwrap();
+    });
+}
+
+#[test]
+fn atomic() { swap_while_reading::<crate::atomic::AtomicStrategy, 2>() }
+
+#[test]
+fn atomic_register() { register_while_swapping::<crate::atomic::AtomicStrategy, 2>() }
+
+#[test]
+fn atomic_pending() { swap_after_pending_swap::<crate::atomic::AtomicStrategy, 2>() }
+
+#[test]
+fn sync() { swap_while_reading::<crate::sync::SyncStrategy, 2>() }
+
+#[test]
+fn sync_register() { register_while_swapping::<crate::sync::SyncStrategy, 2>() }
+
+#[test]
+fn park() { swap_while_reading::<crate::sync::park::ParkStrategy, 2>() }
+
+#[test]
+fn park_atomic() { swap_while_reading::<crate::park::Park<crate::atomic::AtomicStrategy>, 2>() }
+
+#[test]
+fn sync_pending() { swap_after_pending_swap::<crate::sync::SyncStrategy, 2>() }
+
+// a tag freed by one reader may be claimed by another while the writer swaps
+#[test]
+fn sync_reuse() {
+    loom::model(|| {
+        let buffer_data = Arc::new(BufferData::<crate::sync::SyncStrategy, 2>::default());
+        let (r, mut w) = crate::new(buffer_data);
+        let r1 = r.try_clone().unwrap();
+
+        let dropper = thread::spawn(move || drop(r));
+        let reader = thread::spawn(move || {
+            let mut r = r1.try_clone().unwrap();
+            let _ = r.get().read();
+        });
+
+        w.write(1);
+        Writer::swap_buffers(&mut w);
+        w.write(2);
+
+        dropper.join().unwrap();
+        reader.join().unwrap();
+    });
+}
+
+#[test]
+fn sharded() { swap_while_reading::<crate::atomic::ShardedStrategy, 2>() }
+
+#[test]
+fn sharded_register() { register_while_swapping::<crate::atomic::ShardedStrategy, 2>() }
+
+#[test]
+fn sharded_pending() { swap_after_pending_swap::<crate::atomic::ShardedStrategy, 2>() }
+
+#[test]
+fn qsbr() { swap_while_reading::<crate::qsbr::QsbrStrategy, 2>() }
+
+#[test]
+fn qsbr_register() { register_while_swapping::<crate::qsbr::QsbrStrategy, 2>() }
+
+#[test]
+fn triple() { swap_while_reading::<crate::triple::TripleStrategy, 3>() }
+
+#[test]
+fn triple_register() { register_while_swapping::<crate::triple::TripleStrategy, 3>() }
+
+#[test]
+fn triple_pending() { swap_after_pending_swap::<crate::triple::TripleStrategy, 3>() }
+
+#[test]
+fn ring() { swap_while_reading::<crate::ring::RingStrategy<4>, 4>() }
+
+// a reader pinning an old version races with the writer coming back around to its buffer
+#[test]
+fn triple_get_version() {
+    loom::model(|| {
+        let buffer_data = Arc::new(BufferData::<crate::triple::TripleStrategy, 3>::default());
+        let (mut r, mut w) = crate::new(buffer_data);
+
+        let reader = thread::spawn(move || {
+            if let Some(guard) = r.get_version(0) {
+                assert_eq!(guard.read(), 0);
+            }
+        });
+
+        w.write(1);
+        Writer::swap_buffers(&mut w);
+        w.write(2);
+        Writer::swap_buffers(&mut w);
+        w.write(3);
+
+        reader.join().unwrap();
+    });
+}
+
+#[test]
+fn park_sharded() { swap_while_reading::<crate::park::Park<crate::atomic::ShardedStrategy>, 2>() }
+
+#[test]
+fn park_sync() { swap_while_reading::<crate::park::Park<crate::sync::SyncStrategy>, 2>() }
+
+#[test]
+fn park_triple() { swap_while_reading::<crate::park::Park<crate::triple::TripleStrategy>, 3>() }
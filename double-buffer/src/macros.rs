@@ -32,6 +32,7 @@ macro_rules! __imp_make_newtype {
 macro_rules! __imp_newtype_impl_inner {
     (@clone $strategy:ty, $capture_error:ty, core::convert::Infallible $(, $buf_data:lifetime)?) => {
         impl<$($buf_data,)? B, E: ?Sized> Clone for Reader<$($buf_data, )? B, E> {
+            #[track_caller]
             fn clone(&self) -> Self {
                 Self(Clone::clone(&self.0))
             }
@@ -46,14 +47,17 @@ macro_rules! __imp_newtype_impl_inner {
             $crate::raw::ReaderGuard<'reader, BufferRef<$($buf_data,)? B, E>, T>;
         pub type BufferRef<$($buf_data,)? B,E = ()> = BufferRefInternal<$($buf_data,)? B, E>;
 
+        #[track_caller]
         pub fn new<$($buf_data,)? B, E: ?Sized>(buffers: BufferRefInternal<$($buf_data,)? B, E>) -> (Reader<$($buf_data,)? B, E>, Writer<$($buf_data,)? B, E>) {
-            let (reader, writer) = crate::new(buffers);
+            let (reader, writer) = $crate::new(buffers);
             (Reader(reader), Writer(writer))
         }
 
         impl<$($buf_data,)? B, E: ?Sized> Writer<$($buf_data,)? B, E> {
+            #[track_caller]
             pub fn reader(this: &Self) -> Reader<$($buf_data, )? B, E> { Reader($crate::raw::Writer::reader(&this.0)) }
             pub fn read(this: &Self) -> &B { $crate::raw::Writer::read(&this.0) }
+            pub fn version(this: &Self) -> usize { $crate::raw::Writer::version(&this.0) }
             pub fn strategy(this: &Self) -> &$strategy { $crate::raw::Writer::strategy(&this.0) }
             pub fn extra(this: &Self) -> &E { $crate::raw::Writer::extra(&this.0) }
             pub fn split(this: &Self) -> $crate::raw::Split<'_, BufferRef<$($buf_data, )? B, E>> { $crate::raw::Writer::split(&this.0) }
@@ -61,27 +65,114 @@ macro_rules! __imp_newtype_impl_inner {
                 $crate::raw::Writer::split_mut(&mut this.0)
             }
             pub fn swap_buffers(this: &mut Self) { $crate::raw::Writer::swap_buffers(&mut this.0) }
+            pub fn swap_and_sync(this: &mut Self)
+            where
+                B: Clone,
+            {
+                $crate::raw::Writer::swap_and_sync(&mut this.0)
+            }
+            pub fn swap_and_sync_with<F: FnOnce(&B, &mut B)>(this: &mut Self, f: F) {
+                $crate::raw::Writer::swap_and_sync_with(&mut this.0, f)
+            }
+            pub fn try_swap_buffers(this: &mut Self) -> $crate::raw::TrySwap { $crate::raw::Writer::try_swap_buffers(&mut this.0) }
+            pub fn pending_swap(this: &mut Self) -> Option<$crate::raw::PendingSwap<'_, BufferRef<$($buf_data, )? B, E>>> {
+                $crate::raw::Writer::pending_swap(&mut this.0)
+            }
+            pub fn swap_buffers_with_policy<P: $crate::wait::WaitPolicy>(this: &mut Self, policy: P) {
+                $crate::raw::Writer::swap_buffers_with_policy(&mut this.0, policy)
+            }
             pub fn swap_buffers_with<F: FnMut(&Self)>(this: &mut Self, mut f: F) {
                 let f = move |writer: &_| f(unsafe { &*(writer as *const _ as *const Self) });
                 $crate::raw::Writer::swap_buffers_with(&mut this.0, f)
             }
+            /// # Safety
+            ///
+            /// See `raw::Writer::swap_buffers_unchecked`.
             pub unsafe fn swap_buffers_unchecked(this: &mut Self) {
                 $crate::raw::Writer::swap_buffers_unchecked(&mut this.0)
             }
+            /// # Safety
+            ///
+            /// See `raw::Writer::start_buffer_swap`.
             pub unsafe fn start_buffer_swap(this: &mut Self) -> $crate::raw::Swap<BufferRef<$($buf_data, )? B, E>> {
                 $crate::raw::Writer::start_buffer_swap(&mut this.0)
             }
+            /// # Safety
+            ///
+            /// See `raw::Writer::try_start_buffer_swap`.
             pub unsafe fn try_start_buffer_swap(
                 this: &mut Self,
-            ) -> Result<$crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>, $capture_error> {
+            ) -> Result<$crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>, $crate::raw::StartSwapError<$capture_error>> {
                 $crate::raw::Writer::try_start_buffer_swap(&mut this.0)
             }
             pub fn finish_swap(this: &Self, swap: $crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>) {
                 $crate::raw::Writer::finish_swap(&this.0, swap)
             }
+            pub fn finish_swap_with_policy<P: $crate::wait::WaitPolicy>(
+                this: &Self,
+                swap: $crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>,
+                policy: P,
+            ) {
+                $crate::raw::Writer::finish_swap_with_policy(&this.0, swap, policy)
+            }
+            pub fn swap_buffers_async(this: &mut Self) -> $crate::raw::FinishSwap<'_, BufferRef<$($buf_data, )? B, E>> {
+                $crate::raw::Writer::swap_buffers_async(&mut this.0)
+            }
+            pub fn begin_swap(this: &mut Self) -> $crate::raw::PendingSwap<'_, BufferRef<$($buf_data, )? B, E>> {
+                $crate::raw::Writer::begin_swap(&mut this.0)
+            }
+            pub fn finish_swap_async<'this>(
+                this: &'this mut Self,
+                swap: $crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>,
+            ) -> $crate::raw::FinishSwap<'this, BufferRef<$($buf_data, )? B, E>> {
+                $crate::raw::Writer::finish_swap_async(&mut this.0, swap)
+            }
+            pub fn try_finish_swap(
+                this: &Self,
+                swap: $crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>,
+            ) -> Result<(), $crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>> {
+                $crate::raw::Writer::try_finish_swap(&this.0, swap)
+            }
+            #[cfg(feature = "std")]
+            pub fn finish_swap_timeout(
+                this: &Self,
+                swap: $crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>,
+                timeout: std::time::Duration,
+            ) -> Result<(), $crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>> {
+                $crate::raw::Writer::finish_swap_timeout(&this.0, swap, timeout)
+            }
             pub fn finish_swap_with<F: FnMut()>(this: &Self, swap: $crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>, f: F) {
                 $crate::raw::Writer::finish_swap_with(&this.0, swap, f)
             }
+            #[cfg(feature = "std")]
+            pub fn set_watchdog<F>(this: &mut Self, threshold: core::time::Duration, report: F)
+            where
+                F: Fn(&[$crate::diagnostics::ReaderInfo]) + Send + Sync + 'static,
+            {
+                $crate::raw::Writer::set_watchdog(&mut this.0, threshold, report)
+            }
+            #[cfg(feature = "std")]
+            pub fn clear_watchdog(this: &mut Self) { $crate::raw::Writer::clear_watchdog(&mut this.0) }
+            #[cfg(feature = "alloc")]
+            pub fn active_readers(this: &Self) -> std::vec::Vec<$crate::diagnostics::ReaderInfo> {
+                $crate::raw::Writer::active_readers(&this.0)
+            }
+            #[cfg(feature = "alloc")]
+            pub fn blocking_readers(
+                this: &Self,
+                swap: &$crate::raw::Swap<BufferRef<$($buf_data, )? B, E>>,
+            ) -> std::vec::Vec<$crate::diagnostics::ReaderInfo> {
+                $crate::raw::Writer::blocking_readers(&this.0, swap)
+            }
+            #[cfg(feature = "metrics")]
+            pub fn stats(this: &Self) -> $crate::metrics::SwapStats { $crate::raw::Writer::stats(&this.0) }
+            #[cfg(feature = "metrics")]
+            pub fn set_metrics_hook<H: $crate::metrics::MetricsHook + 'static>(
+                this: &Self,
+                hook: H,
+            ) -> Result<(), std::boxed::Box<dyn $crate::metrics::MetricsHook>> {
+                $crate::raw::Writer::set_metrics_hook(&this.0, hook)
+            }
         }
 
         impl<$($buf_data,)? B, E: ?Sized> core::ops::Deref for Writer<$($buf_data,)? B, E> {
@@ -99,10 +190,39 @@ macro_rules! __imp_newtype_impl_inner {
         }
 
         impl<$($buf_data,)? B, E: ?Sized> Reader<$($buf_data,)? B, E> {
+            #[track_caller]
             pub fn try_clone(&self) -> Result<Self, $($upgrade_error)*> { $crate::raw::Reader::try_clone(&self.0).map(Self) }
             pub fn is_dangling(&self) -> bool { $crate::raw::Reader::is_dangling(&self.0) }
             pub fn get(&mut self) -> ReaderGuard<'_, $($buf_data,)? B, B, E> { $crate::raw::Reader::get(&mut self.0) }
             pub fn try_get(&mut self) -> Result<ReaderGuard<'_, $($buf_data,)? B, B, E>, $($upgrade_error)*> { $crate::raw::Reader::try_get(&mut self.0) }
+            pub fn get_if_newer(&mut self, last_seen: usize) -> Option<ReaderGuard<'_, $($buf_data,)? B, B, E>> {
+                $crate::raw::Reader::get_if_newer(&mut self.0, last_seen)
+            }
+            pub fn try_get_if_newer(
+                &mut self,
+                last_seen: usize,
+            ) -> Result<Option<ReaderGuard<'_, $($buf_data,)? B, B, E>>, $($upgrade_error)*> {
+                $crate::raw::Reader::try_get_if_newer(&mut self.0, last_seen)
+            }
+            pub fn get_version(&mut self, version: usize) -> Option<ReaderGuard<'_, $($buf_data,)? B, B, E>> {
+                $crate::raw::Reader::get_version(&mut self.0, version)
+            }
+            pub fn try_get_version(
+                &mut self,
+                version: usize,
+            ) -> Result<Option<ReaderGuard<'_, $($buf_data,)? B, B, E>>, $($upgrade_error)*> {
+                $crate::raw::Reader::try_get_version(&mut self.0, version)
+            }
+            #[cfg(feature = "alloc")]
+            pub fn changed(&self) -> $crate::raw::Changed<'_, BufferRef<$($buf_data,)? B, E>> { $crate::raw::Reader::changed(&self.0) }
+            #[cfg(feature = "std")]
+            pub fn wait_for_swap(&self) -> Result<(), $crate::raw::Closed> { $crate::raw::Reader::wait_for_swap(&self.0) }
+            #[cfg(feature = "std")]
+            pub fn wait_for_swap_timeout(&self, timeout: std::time::Duration) -> Result<bool, $crate::raw::Closed> {
+                $crate::raw::Reader::wait_for_swap_timeout(&self.0, timeout)
+            }
+            #[cfg(feature = "alloc")]
+            pub fn set_name<N: Into<std::borrow::Cow<'static, str>>>(&mut self, name: N) { $crate::raw::Reader::set_name(&mut self.0, name) }
         }
 
         $crate::__imp_newtype_impl_inner!{@clone $strategy, $capture_error, $($upgrade_error)* $(, $buf_data)?}
@@ -0,0 +1,91 @@
+use crate::loom::atomic::{AtomicU64, Ordering};
+use core::time::Duration;
+use std::{boxed::Box, sync::OnceLock};
+
+// receives every event as it is recorded, for example to forward it to a tracing or metrics pipeline
+//
+// hooks are called from both the writer and the readers, so they should be cheap
+pub trait MetricsHook: Send + Sync {
+    // the buffers were swapped, and the swap has to wait for `captured_readers` readers
+    fn swap_started(&self, captured_readers: usize) { let _ = captured_readers; }
+
+    // the last reader of the old buffer exited `wait_time` after the buffers were swapped
+    fn swap_finished(&self, wait_time: Duration) { let _ = wait_time; }
+
+    fn guard_released(&self, hold_time: Duration) { let _ = hold_time; }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct SwapStats {
+    pub swaps: u64,
+    pub finished_swaps: u64,
+    pub captured_readers: u64,
+    pub wait_time: Duration,
+    pub max_wait_time: Duration,
+    pub guards: u64,
+    pub guard_time: Duration,
+    pub max_guard_time: Duration,
+}
+
+#[derive(Default)]
+pub(crate) struct Metrics {
+    swaps: AtomicU64,
+    finished_swaps: AtomicU64,
+    captured_readers: AtomicU64,
+    wait_nanos: AtomicU64,
+    max_wait_nanos: AtomicU64,
+    guards: AtomicU64,
+    guard_nanos: AtomicU64,
+    max_guard_nanos: AtomicU64,
+    hook: OnceLock<Box<dyn MetricsHook>>,
+}
+
+fn nanos(duration: Duration) -> u64 { duration.as_nanos().min(u64::MAX as u128) as u64 }
+
+impl Metrics {
+    pub fn set_hook(&self, hook: Box<dyn MetricsHook>) -> Result<(), Box<dyn MetricsHook>> { self.hook.set(hook) }
+
+    pub fn swap_started(&self, captured_readers: usize) {
+        self.swaps.fetch_add(1, Ordering::Relaxed);
+        self.captured_readers.fetch_add(captured_readers as u64, Ordering::Relaxed);
+
+        if let Some(hook) = self.hook.get() {
+            hook.swap_started(captured_readers)
+        }
+    }
+
+    pub fn swap_finished(&self, wait_time: Duration) {
+        self.finished_swaps.fetch_add(1, Ordering::Relaxed);
+        self.wait_nanos.fetch_add(nanos(wait_time), Ordering::Relaxed);
+        self.max_wait_nanos.fetch_max(nanos(wait_time), Ordering::Relaxed);
+
+        if let Some(hook) = self.hook.get() {
+            hook.swap_finished(wait_time)
+        }
+    }
+
+    pub fn guard_released(&self, hold_time: Duration) {
+        self.guards.fetch_add(1, Ordering::Relaxed);
+        self.guard_nanos.fetch_add(nanos(hold_time), Ordering::Relaxed);
+        self.max_guard_nanos.fetch_max(nanos(hold_time), Ordering::Relaxed);
+
+        if let Some(hook) = self.hook.get() {
+            hook.guard_released(hold_time)
+        }
+    }
+
+    // each counter is read separately, so a snapshot taken while readers are
+    // active may be slightly inconsistent
+    pub fn stats(&self) -> SwapStats {
+        SwapStats {
+            swaps: self.swaps.load(Ordering::Relaxed),
+            finished_swaps: self.finished_swaps.load(Ordering::Relaxed),
+            captured_readers: self.captured_readers.load(Ordering::Relaxed),
+            wait_time: Duration::from_nanos(self.wait_nanos.load(Ordering::Relaxed)),
+            max_wait_time: Duration::from_nanos(self.max_wait_nanos.load(Ordering::Relaxed)),
+            guards: self.guards.load(Ordering::Relaxed),
+            guard_time: Duration::from_nanos(self.guard_nanos.load(Ordering::Relaxed)),
+            max_guard_time: Duration::from_nanos(self.max_guard_nanos.load(Ordering::Relaxed)),
+        }
+    }
+}
@@ -0,0 +1,86 @@
+#[cfg(feature = "std")]
+use crate::loom::Condvar;
+use crate::loom::{
+    atomic::{self, AtomicBool, Ordering},
+    Mutex,
+};
+use core::task::Waker;
+use std::vec::Vec;
+
+#[derive(Default)]
+pub(crate) struct Notify {
+    closed: AtomicBool,
+    has_waiters: AtomicBool,
+    wakers: Mutex<Vec<Waker>>,
+    #[cfg(feature = "std")]
+    cv: Condvar,
+}
+
+impl Notify {
+    #[inline]
+    pub fn is_closed(&self) -> bool { self.closed.load(Ordering::SeqCst) }
+
+    #[inline]
+    pub fn notify(&self) {
+        atomic::fence(Ordering::SeqCst);
+
+        if self.has_waiters.load(Ordering::Relaxed) {
+            self.notify_slow()
+        }
+    }
+
+    pub fn close(&self) {
+        self.closed.store(true, Ordering::SeqCst);
+
+        if self.has_waiters.load(Ordering::SeqCst) {
+            self.notify_slow()
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn notify_slow(&self) {
+        let wakers = {
+            let mut wakers = self.wakers.lock();
+            self.has_waiters.store(false, Ordering::SeqCst);
+            core::mem::take(&mut *wakers)
+        };
+
+        #[cfg(feature = "std")]
+        self.cv.notify_all();
+
+        wakers.into_iter().for_each(Waker::wake);
+    }
+
+    pub fn register(&self, waker: &Waker) {
+        let mut wakers = self.wakers.lock();
+
+        if !wakers.iter().any(|old| old.will_wake(waker)) {
+            wakers.push(waker.clone());
+        }
+
+        self.has_waiters.store(true, Ordering::SeqCst);
+    }
+
+    #[cfg(feature = "std")]
+    pub fn wait<F: FnMut() -> bool>(&self, mut is_notified: F, deadline: Option<std::time::Instant>) -> bool {
+        let mut wakers = self.wakers.lock();
+
+        loop {
+            self.has_waiters.store(true, Ordering::SeqCst);
+
+            if is_notified() {
+                return true
+            }
+
+            match deadline {
+                None => self.cv.wait(&mut wakers),
+                Some(deadline) => {
+                    if self.cv.wait_until(&mut wakers, deadline).timed_out() {
+                        return is_notified()
+                    }
+                }
+            }
+        }
+    }
+}
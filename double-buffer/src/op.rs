@@ -101,7 +101,7 @@ impl<B: BufferRef, O: Operation<B::Buffer>> WriterRef<'_, B, O> {
     }
 
     #[inline]
-    pub fn operations(&self) -> &[O] { &self.ops }
+    pub fn operations(&self) -> &[O] { self.ops }
 
     #[inline]
     pub fn by_ref(&mut self) -> WriterRef<'_, B, O> {
@@ -0,0 +1,184 @@
+use crate::{
+    diagnostics::ReaderInfo,
+    loom::{
+        atomic::{self, AtomicBool, Ordering},
+        Condvar, Mutex,
+    },
+    Strategy,
+};
+use core::{
+    task::{Context, Poll},
+    time::Duration,
+};
+use std::borrow::Cow;
+
+pub type BufferData<S, B, E = ()> = crate::BufferData<<S as Strategy>::Whitch, Park<S>, B, E>;
+
+// readers of some strategies may exit without going through `end_guard` (for example by going
+// quiescent), so by default a parked writer still checks on the readers of those periodically
+const PARK_TIMEOUT: Duration = Duration::from_millis(1);
+
+pub struct Park<S> {
+    raw: S,
+    is_parked: AtomicBool,
+    lock: Mutex<()>,
+    cv: Condvar,
+    timeout: Option<Duration>,
+}
+
+impl<S: Strategy + Default> Default for Park<S> {
+    fn default() -> Self { Self::new(S::default()) }
+}
+
+impl<S: Strategy> Park<S> {
+    pub fn new(raw: S) -> Self {
+        let timeout = if S::READERS_EXIT_IN_END_GUARD {
+            None
+        } else {
+            Some(PARK_TIMEOUT)
+        };
+
+        Self::with_timeout(raw, timeout)
+    }
+
+    // the longest the writer stays parked before checking on the readers again,
+    // `None` leaves it to `end_guard` to wake the writer
+    pub fn with_timeout(raw: S, timeout: Option<Duration>) -> Self {
+        Self {
+            raw,
+            is_parked: AtomicBool::new(false),
+            lock: Mutex::new(()),
+            cv: Condvar::new(),
+            timeout,
+        }
+    }
+}
+
+impl<S> Park<S> {
+    pub fn get(&self) -> &S { &self.raw }
+
+    #[cold]
+    #[inline(never)]
+    fn unpark(&self) {
+        let _lock = self.lock.lock();
+        self.cv.notify_all();
+    }
+}
+
+unsafe impl<S: Strategy> Strategy for Park<S> {
+    type Whitch = S::Whitch;
+    type ReaderTag = S::ReaderTag;
+    type WriterTag = S::WriterTag;
+    type RawGuard = S::RawGuard;
+
+    type FastCapture = S::FastCapture;
+    type CaptureError = S::CaptureError;
+    type Capture = S::Capture;
+
+    const BUFFER_COUNT: Option<usize> = S::BUFFER_COUNT;
+    const READERS_EXIT_IN_END_GUARD: bool = S::READERS_EXIT_IN_END_GUARD;
+
+    #[inline]
+    unsafe fn reader_tag(&self) -> Self::ReaderTag { self.raw.reader_tag() }
+
+    #[inline]
+    unsafe fn writer_tag(&self) -> Self::WriterTag { self.raw.writer_tag() }
+
+    #[inline]
+    fn has_active_readers(&self, tag: &mut Self::WriterTag) -> bool { self.raw.has_active_readers(tag) }
+
+    #[inline]
+    fn try_capture_readers(&self, tag: &mut Self::WriterTag) -> Result<Self::FastCapture, Self::CaptureError> {
+        self.raw.try_capture_readers(tag)
+    }
+
+    #[inline]
+    fn finish_capture_readers(&self, tag: &mut Self::WriterTag, capture: Self::FastCapture) -> Self::Capture {
+        self.raw.finish_capture_readers(tag, capture)
+    }
+
+    #[inline]
+    fn readers_have_exited(&self, capture: &mut Self::Capture) -> bool { self.raw.readers_have_exited(capture) }
+
+    #[inline]
+    fn poll_readers_have_exited(&self, capture: &mut Self::Capture, ctx: &mut Context<'_>) -> Poll<()> {
+        self.raw.poll_readers_have_exited(capture, ctx)
+    }
+
+    fn park_writer(&self, capture: &mut Self::Capture, timeout: Option<Duration>) {
+        let mut lock = self.lock.lock();
+        self.is_parked.store(true, Ordering::SeqCst);
+        // either this sees the reader's exit, or the reader sees that the writer is parked, see `end_guard`
+        atomic::fence(Ordering::SeqCst);
+
+        if !self.raw.readers_have_exited(capture) {
+            let timeout = match (timeout, self.timeout) {
+                (Some(timeout), Some(max)) => Some(timeout.min(max)),
+                (timeout, max) => timeout.or(max),
+            };
+
+            match timeout {
+                None => self.cv.wait(&mut lock),
+                Some(timeout) => drop(self.cv.wait_for(&mut lock, timeout)),
+            }
+        }
+
+        self.is_parked.store(false, Ordering::Relaxed);
+    }
+
+    #[inline]
+    fn begin_guard(&self, tag: &mut Self::ReaderTag) -> Self::RawGuard { self.raw.begin_guard(tag) }
+
+    #[inline]
+    fn begin_guard_latest(&self, tag: &mut Self::ReaderTag, which: &Self::Whitch) -> (Self::RawGuard, usize) {
+        self.raw.begin_guard_latest(tag, which)
+    }
+
+    #[inline]
+    fn begin_guard_at(&self, tag: &mut Self::ReaderTag, which: &Self::Whitch, version: usize) -> Option<(Self::RawGuard, usize)> {
+        self.raw.begin_guard_at(tag, which, version)
+    }
+
+    #[inline]
+    fn write_index(&self, tag: &Self::WriterTag) -> Option<usize> { self.raw.write_index(tag) }
+
+    #[inline]
+    fn published_index(&self, tag: &Self::WriterTag) -> Option<usize> { self.raw.published_index(tag) }
+
+    #[inline]
+    fn guard_index(&self, guard: &Self::RawGuard) -> Option<usize> { self.raw.guard_index(guard) }
+
+    #[inline]
+    fn end_guard(&self, guard: Self::RawGuard) {
+        self.raw.end_guard(guard);
+
+        // a SeqCst read-modify-write that releases the reader is ordered before the SeqCst load below,
+        // so the fence is only needed for other strategies. Loom treats SeqCst accesses as AcqRel, so
+        // it doesn't see that ordering and still needs the fence
+        if !S::READERS_EXIT_IN_END_GUARD || cfg!(loom) {
+            atomic::fence(Ordering::SeqCst);
+        }
+
+        if self.is_parked.load(Ordering::SeqCst) {
+            self.unpark()
+        }
+    }
+
+    #[inline]
+    fn describe_reader<F: FnOnce() -> ReaderInfo>(&self, tag: &mut Self::ReaderTag, info: F) {
+        self.raw.describe_reader(tag, info)
+    }
+
+    #[inline]
+    fn name_reader(&self, tag: &mut Self::ReaderTag, name: Cow<'static, str>) { self.raw.name_reader(tag, name) }
+
+    #[inline]
+    fn active_readers(&self) -> Vec<ReaderInfo> { self.raw.active_readers() }
+
+    #[inline]
+    fn blocking_readers(&self, capture: &Self::Capture) -> Vec<ReaderInfo> { self.raw.blocking_readers(capture) }
+
+    #[cfg(feature = "metrics")]
+    #[inline]
+    fn captured_readers(&self, capture: &Self::Capture) -> usize { self.raw.captured_readers(capture) }
+}
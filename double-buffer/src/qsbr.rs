@@ -0,0 +1,168 @@
+use crate::{
+    loom::{
+        atomic::{self, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thin::Arc,
+    waker::AtomicWaker,
+    Strategy,
+};
+use core::task::{Context, Poll};
+use smallvec::SmallVec;
+
+pub type BufferData<B, E = ()> = crate::BufferData<AtomicUsize, QsbrStrategy, B, E>;
+
+crate::__imp_make_newtype! {
+    crate::qsbr::QsbrStrategy, core::convert::Infallible, ArcInner, std::sync::Arc
+}
+
+impl<B, E: ?Sized> owned::Reader<B, E> {
+    pub fn quiescent(&mut self) { self.0.quiescent() }
+}
+
+impl<B, E: ?Sized> thin::Reader<B, E> {
+    pub fn quiescent(&mut self) { self.0.quiescent() }
+}
+
+impl<B, E: ?Sized> reference::Reader<'_, B, E> {
+    pub fn quiescent(&mut self) { self.0.quiescent() }
+}
+
+// stored in a tag once its reader is dropped, so it never holds up a swap
+const OFFLINE: usize = usize::MAX;
+
+#[derive(Default)]
+pub struct QsbrStrategy {
+    tag_list: Mutex<SmallVec<[Arc<AtomicUsize>; 8]>>,
+    epoch: AtomicUsize,
+    waker: std::sync::Arc<AtomicWaker>,
+}
+
+pub struct RawGuard(());
+
+pub struct Capture {
+    epoch: usize,
+    active: SmallVec<[Arc<AtomicUsize>; 8]>,
+}
+
+pub struct ReaderTag {
+    // the last epoch in which this reader announced a quiescent state
+    epoch: Arc<AtomicUsize>,
+    waker: std::sync::Arc<AtomicWaker>,
+}
+
+pub struct WriterTag(());
+
+fn is_quiescent(tag: &AtomicUsize, epoch: usize) -> bool {
+    match tag.load(Ordering::Acquire) {
+        OFFLINE => true,
+        tag => tag.wrapping_sub(epoch) as isize >= 0,
+    }
+}
+
+impl QsbrStrategy {
+    #[inline]
+    pub(crate) fn quiescent(&self, tag: &mut ReaderTag) {
+        let epoch = self.epoch.load(Ordering::SeqCst);
+
+        if tag.epoch.load(Ordering::Relaxed) != epoch {
+            tag.epoch.store(epoch, Ordering::Release);
+            atomic::fence(Ordering::SeqCst);
+
+            if self.waker.is_registered() {
+                self.waker.wake()
+            }
+        }
+    }
+}
+
+impl Drop for ReaderTag {
+    fn drop(&mut self) {
+        self.epoch.store(OFFLINE, Ordering::Release);
+        atomic::fence(Ordering::SeqCst);
+
+        if self.waker.is_registered() {
+            self.waker.wake()
+        }
+    }
+}
+
+unsafe impl Strategy for QsbrStrategy {
+    type Whitch = AtomicUsize;
+    type ReaderTag = ReaderTag;
+    type WriterTag = WriterTag;
+    type RawGuard = RawGuard;
+
+    type FastCapture = ();
+    type CaptureError = core::convert::Infallible;
+    type Capture = Capture;
+
+    #[inline]
+    unsafe fn reader_tag(&self) -> Self::ReaderTag {
+        let epoch = Arc::new(AtomicUsize::new(self.epoch.load(Ordering::SeqCst)));
+        self.tag_list.lock().push(epoch.clone());
+        ReaderTag {
+            epoch,
+            waker: self.waker.clone(),
+        }
+    }
+
+    #[inline]
+    unsafe fn writer_tag(&self) -> Self::WriterTag { WriterTag(()) }
+
+    // a reader may be in the middle of a read at any time until it is dropped
+    fn has_active_readers(&self, _: &mut Self::WriterTag) -> bool {
+        self.tag_list.lock().iter().any(|tag| tag.load(Ordering::Relaxed) != OFFLINE)
+    }
+
+    #[inline]
+    fn try_capture_readers(&self, _: &mut Self::WriterTag) -> Result<Self::FastCapture, Self::CaptureError> { Ok(()) }
+
+    fn finish_capture_readers(&self, _: &mut Self::WriterTag, (): Self::FastCapture) -> Self::Capture {
+        let epoch = self.epoch.fetch_add(1, Ordering::SeqCst).wrapping_add(1);
+        let mut active = SmallVec::new();
+
+        self.tag_list.lock().retain(|tag| {
+            let is_alive = Arc::strong_count(tag) != 1;
+
+            if is_alive && !is_quiescent(tag, epoch) {
+                active.push(tag.clone())
+            }
+
+            is_alive
+        });
+
+        Capture { epoch, active }
+    }
+
+    #[inline]
+    fn readers_have_exited(&self, capture: &mut Self::Capture) -> bool {
+        let epoch = capture.epoch;
+        capture.active.retain(|tag| !is_quiescent(tag, epoch));
+        capture.active.is_empty()
+    }
+
+    fn poll_readers_have_exited(&self, capture: &mut Self::Capture, ctx: &mut Context<'_>) -> Poll<()> {
+        if self.readers_have_exited(capture) {
+            return Poll::Ready(())
+        }
+
+        self.waker.register(ctx.waker());
+        atomic::fence(Ordering::SeqCst);
+
+        if self.readers_have_exited(capture) {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    #[inline]
+    fn begin_guard(&self, _: &mut Self::ReaderTag) -> Self::RawGuard { RawGuard(()) }
+
+    #[inline]
+    fn end_guard(&self, _: Self::RawGuard) {}
+
+    #[cfg(feature = "metrics")]
+    fn captured_readers(&self, capture: &Self::Capture) -> usize { capture.active.len() }
+}
@@ -1,47 +1,135 @@
 use core::{
     cell::UnsafeCell,
+    future::Future,
     mem::ManuallyDrop,
     ops::{Deref, DerefMut},
+    pin::Pin,
     sync::atomic::Ordering,
+    task::{Context, Poll},
 };
 
-use crate::*;
+use crate::{
+    wait::{Parker, SpinThenPark, WaitPolicy},
+    *,
+};
 
 pub struct Writer<B: BufferRef> {
     inner: B::Strong,
     tag: WriterTag<B>,
+    swap: Option<Swap<B>>,
+    #[cfg(feature = "std")]
+    watchdog: Option<(core::time::Duration, Report)>,
 }
 
+#[cfg(feature = "std")]
+type Report = std::boxed::Box<dyn Fn(&[crate::diagnostics::ReaderInfo]) + Send + Sync>;
+
+// the tag is dropped first, so that strategies may rely on it not outliving them
 pub struct Reader<B: BufferRef> {
-    inner: B::Weak,
     tag: ReaderTag<B>,
+    inner: B::Weak,
+    // the latest version that this reader got, so it can tell whether it missed a swap
+    last_seen: usize,
 }
 
 pub struct ReaderGuard<'reader, B: BufferRef, T: ?Sized = <B as BufferRef>::Buffer> {
     value: &'reader T,
-    raw: RawGuard<B>,
+    raw: RawGuard<'reader, B>,
 }
 
-pub struct RawGuard<B: BufferRef> {
+pub struct RawGuard<'reader, B: BufferRef> {
     raw: ManuallyDrop<<B::Strategy as Strategy>::RawGuard>,
-    keep_alive: B::Strong,
+    version: usize,
+    inner: &'reader BufferRefData<B>,
+    #[cfg(feature = "detect-deadlock")]
+    index: usize,
+    #[cfg(feature = "detect-deadlock")]
+    guards: crate::deadlock::Guards,
+    #[cfg(feature = "metrics")]
+    acquired: std::time::Instant,
 }
 
-impl<B: BufferRef> Drop for RawGuard<B> {
-    fn drop(&mut self) { unsafe { self.keep_alive.strategy.end_guard(ManuallyDrop::take(&mut self.raw)) } }
+impl<B: BufferRef> Drop for RawGuard<'_, B> {
+    fn drop(&mut self) {
+        #[cfg(feature = "detect-deadlock")]
+        crate::deadlock::release(&self.guards, self.inner as *const _ as *const () as usize, self.index);
+
+        #[cfg(feature = "metrics")]
+        let hold_time = self.acquired.elapsed();
+
+        unsafe { self.inner.strategy.end_guard(ManuallyDrop::take(&mut self.raw)) }
+
+        #[cfg(feature = "metrics")]
+        self.inner.metrics.guard_released(hold_time);
+    }
 }
 
-pub struct Buffers<B>(UnsafeCell<[B; 2]>);
+pub struct Buffers<B, C: BufferCount = Count<2>>(UnsafeCell<C::Array<B>>);
 
-pub struct BufferData<W, S, B, E: ?Sized> {
+pub struct BufferData<W, S, B, E: ?Sized, C: BufferCount = Count<2>> {
     which: W,
-    pub buffers: Buffers<B>,
+    #[cfg(feature = "alloc")]
+    notify: crate::notify::Notify,
+    #[cfg(feature = "metrics")]
+    metrics: crate::metrics::Metrics,
+    pub buffers: Buffers<B, C>,
     pub strategy: S,
     pub extra: E,
 }
 
 pub struct Swap<B: BufferRef> {
     capture: Capture<B>,
+    start: SwapStart,
+}
+
+#[derive(Clone, Copy)]
+struct SwapStart {
+    #[cfg(feature = "metrics")]
+    time: std::time::Instant,
+}
+
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct FinishSwap<'a, B: BufferRef> {
+    writer: &'a mut Writer<B>,
+    start: bool,
+}
+
+// the swap is kept in the writer's pending swap slot, so dropping this without
+// waiting only defers the wait to the next time the write buffer is accessed
+#[must_use = "the write buffer is only accessible after waiting for the swap"]
+pub struct PendingSwap<'a, B: BufferRef> {
+    writer: &'a mut Writer<B>,
+}
+
+#[cfg(feature = "alloc")]
+#[must_use = "futures do nothing unless you `.await` or poll them"]
+pub struct Changed<'a, B: BufferRef> {
+    reader: &'a Reader<B>,
+}
+
+#[derive(Debug)]
+pub struct Closed;
+
+#[must_use]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrySwap {
+    // readers were active, or an earlier swap is still pending, so nothing was published
+    NotSwapped,
+    Completed,
+    // the buffers were published, but a reader started before the swap and is still
+    // reading the new write buffer, see `Writer::pending_swap`
+    Pending,
+}
+
+#[derive(Debug)]
+pub enum StartSwapError<E> {
+    // an earlier swap is still waiting on readers, see `Writer::pending_swap`
+    Pending,
+    Capture(E),
+}
+
+impl<B: BufferRef> core::fmt::Debug for Swap<B> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result { f.debug_struct("Swap").finish_non_exhaustive() }
 }
 
 #[non_exhaustive]
@@ -63,50 +151,134 @@ impl<'a, B: BufferRef> Clone for Split<'a, B> {
     fn clone(&self) -> Self { *self }
 }
 
+#[track_caller]
 pub fn new<B: BufferRef>(buffer_ref: B) -> (Reader<B>, Writer<B>) {
     let (writer, reader) = buffer_ref.split();
-    writer.which.store(false, Ordering::Release);
-    let reader_tag = unsafe { writer.strategy.reader_tag() };
+    writer.which.store(0, Ordering::Release);
+    let reader_tag = reader_tag::<B>(&writer.strategy);
     let writer_tag = unsafe { writer.strategy.writer_tag() };
     (
         Reader {
             inner: reader,
             tag: reader_tag,
+            last_seen: 0,
         },
         Writer {
             inner: writer,
             tag: writer_tag,
+            swap: None,
+            #[cfg(feature = "std")]
+            watchdog: None,
         },
     )
 }
 
+#[inline]
+#[track_caller]
+fn reader_tag<B: BufferRef>(strategy: &B::Strategy) -> ReaderTag<B> {
+    #[allow(unused_mut)]
+    let mut tag = unsafe { strategy.reader_tag() };
+    #[cfg(feature = "alloc")]
+    {
+        let location = core::panic::Location::caller();
+        strategy.describe_reader(&mut tag, || {
+            let info = crate::diagnostics::ReaderInfo::with_location(location);
+            // capturing a backtrace for every reader is expensive, so it has to be asked for
+            #[cfg(feature = "reader-backtraces")]
+            let info = info.with_backtrace();
+            info
+        });
+    }
+    tag
+}
+
 #[cold]
-fn snooze(backoff: &crossbeam_utils::Backoff) { backoff.snooze() }
+fn wait<B: BufferRef>(
+    strategy: &B::Strategy,
+    swap: &mut Swap<B>,
+    policy: &mut dyn WaitPolicy,
+    timeout: Option<core::time::Duration>,
+) {
+    let mut park = |timeout| strategy.park_writer(&mut swap.capture, timeout);
+    policy.wait(Parker::new(&mut park, timeout));
+    crate::loom::spin_wait()
+}
 
-#[derive(Default)]
-pub struct BufferDataBuilder<S, B, E> {
-    pub buffers: [B; 2],
+pub struct BufferDataBuilder<S, B, E, const N: usize = 2> {
+    pub buffers: [B; N],
     pub strategy: S,
     pub extra: E,
 }
 
-unsafe impl<B: Send> Send for Buffers<B> {}
-unsafe impl<B: Sync> Sync for Buffers<B> {}
+unsafe impl<B: Send, C: BufferCount> Send for Buffers<B, C> {}
+unsafe impl<B: Sync, C: BufferCount> Sync for Buffers<B, C> {}
+
+// versions wrap around at a multiple of the number of buffers, so that every version keeps
+// mapping to the same buffer. Unless the strategy picks the buffers itself, the published buffer
+// is `version % N`, and the writer writes to the next one
+#[inline]
+fn version_wrap<C: BufferCount>() -> Option<usize> {
+    if C::N.is_power_of_two() {
+        None
+    } else {
+        Some(usize::MAX - usize::MAX % C::N)
+    }
+}
 
-impl<B> Buffers<B> {
-    fn get_raw(&self, item: bool) -> *mut B { unsafe { self.0.get().cast::<B>().offset(isize::from(item)) } }
-    fn read_buffer(&self, item: bool) -> *mut B { self.get_raw(item) }
-    fn write_buffer(&self, item: bool) -> *mut B { self.get_raw(!item) }
+#[inline]
+pub(crate) fn next_version<C: BufferCount>(version: usize) -> usize {
+    match version_wrap::<C>() {
+        Some(wrap) if version + 1 == wrap => 0,
+        _ => version.wrapping_add(1),
+    }
+}
 
-    pub fn get(&self) -> &[B; 2] { unsafe { &*self.0.get() } }
+// the number of swaps since `version` was published, if it was ever valid
+#[inline]
+fn version_age<C: BufferCount>(latest: usize, version: usize) -> usize {
+    match version_wrap::<C>() {
+        None => latest.wrapping_sub(version),
+        Some(wrap) if version >= wrap => usize::MAX,
+        Some(_) if version <= latest => latest - version,
+        Some(wrap) => latest + (wrap - version),
+    }
+}
+
+impl<B, C: BufferCount> Buffers<B, C> {
+    fn get_raw(&self, index: usize) -> *mut B {
+        debug_assert!(index < C::N);
+        unsafe { self.0.get().cast::<B>().add(index) }
+    }
+
+    pub fn get(&self) -> &C::Array<B> { unsafe { &*self.0.get() } }
 
-    pub fn get_mut(&mut self) -> &mut [B; 2] { unsafe { &mut *self.0.get() } }
+    pub fn get_mut(&mut self) -> &mut C::Array<B> { unsafe { &mut *self.0.get() } }
+}
+
+impl<S: Default, B: Default, E: Default, const N: usize> Default for BufferDataBuilder<S, B, E, N> {
+    fn default() -> Self {
+        Self {
+            buffers: core::array::from_fn(|_| B::default()),
+            strategy: S::default(),
+            extra: E::default(),
+        }
+    }
 }
 
-impl<B, S: Strategy, E> BufferDataBuilder<S, B, E> {
-    pub fn build<W: TrustedRadium<Item = bool>>(self) -> BufferData<W, S, B, E> {
+impl<B, S: Strategy, E, const N: usize> BufferDataBuilder<S, B, E, N> {
+    pub fn build<W: TrustedRadium<Item = usize>>(self) -> BufferData<W, S, B, E, Count<N>> {
+        assert!(N >= 2, "Tried to build buffer data with less than two buffers");
+
+        if let Some(count) = S::BUFFER_COUNT {
+            assert_eq!(count, N, "Tried to build buffer data with the wrong number of buffers for its strategy");
+        }
+
         BufferData {
-            which: W::new(false),
+            which: W::new(0),
+            #[cfg(feature = "alloc")]
+            notify: Default::default(),
+            #[cfg(feature = "metrics")]
+            metrics: Default::default(),
             buffers: Buffers(UnsafeCell::new(self.buffers)),
             strategy: self.strategy,
             extra: self.extra,
@@ -114,9 +286,9 @@ impl<B, S: Strategy, E> BufferDataBuilder<S, B, E> {
     }
 }
 
-impl<W, B: Default, S> Default for BufferData<W, S, B, ()>
+impl<W, B: Default, S, const N: usize> Default for BufferData<W, S, B, (), Count<N>>
 where
-    W: TrustedRadium<Item = bool>,
+    W: TrustedRadium<Item = usize>,
     B: Default,
     S: Default + Strategy,
 {
@@ -126,13 +298,22 @@ where
 
 impl<W, B, S> BufferData<W, S, B, ()>
 where
-    W: TrustedRadium<Item = bool>,
+    W: TrustedRadium<Item = usize>,
+    S: Default + Strategy,
+{
+    #[inline]
+    pub fn new(front: B, back: B) -> Self { BufferData::from_buffers([front, back]) }
+}
+
+impl<W, B, S, const N: usize> BufferData<W, S, B, (), Count<N>>
+where
+    W: TrustedRadium<Item = usize>,
     S: Default + Strategy,
 {
     #[inline]
-    pub fn new(front: B, back: B) -> Self {
+    pub fn from_buffers(buffers: [B; N]) -> Self {
         BufferDataBuilder {
-            buffers: [front, back],
+            buffers,
             strategy: Default::default(),
             extra: Default::default(),
         }
@@ -140,7 +321,12 @@ where
     }
 }
 
-impl<B, S, E: ?Sized> BufferData<S::Whitch, S, B, E>
+impl<W, S, B, E: ?Sized, C: BufferCount> BufferData<W, S, B, E, C> {
+    #[cfg(feature = "alloc")]
+    pub(crate) fn is_writer_dropped(&self) -> bool { self.notify.is_closed() }
+}
+
+impl<B, S, E: ?Sized, C: BufferCount> BufferData<S::Whitch, S, B, E, C>
 where
     S: Default + Strategy,
 {
@@ -150,7 +336,7 @@ where
 struct FinishSwapOnDrop<'a, B: BufferRef> {
     strategy: &'a B::Strategy,
     swap: Swap<B>,
-    backoff: crossbeam_utils::Backoff,
+    policy: SpinThenPark,
 }
 
 pub(super) fn is_swap_completed<B: BufferRef>(strategy: &B::Strategy, swap: &mut Swap<B>) -> bool {
@@ -167,31 +353,45 @@ impl<B: BufferRef> Drop for FinishSwapOnDrop<'_, B> {
     #[inline]
     fn drop(&mut self) {
         while !self.strategy.is_swap_completed(&mut self.swap) {
-            snooze(&self.backoff)
+            wait(self.strategy, &mut self.swap, &mut self.policy, None)
         }
     }
 }
 
 impl<B: BufferRef> Writer<B> {
     #[inline]
+    #[track_caller]
     pub fn reader(this: &Self) -> Reader<B> {
-        let tag = unsafe { this.inner.strategy.reader_tag() };
+        let tag = reader_tag::<B>(&this.inner.strategy);
         Reader {
             tag,
             inner: B::downgrade(&this.inner),
+            last_seen: this.inner.which.load(Ordering::Acquire),
         }
     }
 
     #[inline]
-    pub fn read(this: &Self) -> &B::Buffer {
-        unsafe {
-            let inner = &*this.inner;
-            let which = inner.which.load_unsync();
-            let read_buffer = inner.buffers.read_buffer(which);
-            &*read_buffer
+    fn published_index(this: &Self) -> usize {
+        match this.inner.strategy.published_index(&this.tag) {
+            Some(index) => index,
+            None => unsafe { this.inner.which.load_unsync() % <B::Count as BufferCount>::N },
+        }
+    }
+
+    #[inline]
+    fn write_index(this: &Self) -> usize {
+        match this.inner.strategy.write_index(&this.tag) {
+            Some(index) => index,
+            None => unsafe { this.inner.which.load_unsync().wrapping_add(1) % <B::Count as BufferCount>::N },
         }
     }
 
+    #[inline]
+    pub fn read(this: &Self) -> &B::Buffer { unsafe { &*this.inner.buffers.get_raw(Self::published_index(this)) } }
+
+    #[inline]
+    pub fn version(this: &Self) -> usize { unsafe { this.inner.which.load_unsync() } }
+
     #[inline]
     pub fn strategy(this: &Self) -> &B::Strategy { &this.inner.strategy }
 
@@ -202,9 +402,8 @@ impl<B: BufferRef> Writer<B> {
     pub fn split(this: &Self) -> Split<'_, B> {
         unsafe {
             let inner = &*this.inner;
-            let which = inner.which.load_unsync();
-            let reader = inner.buffers.read_buffer(which);
-            let writer = inner.buffers.write_buffer(which);
+            let reader = inner.buffers.get_raw(Self::published_index(this));
+            let writer = inner.buffers.get_raw(Self::write_index(this));
 
             Split {
                 read: &*reader,
@@ -216,11 +415,12 @@ impl<B: BufferRef> Writer<B> {
 
     #[inline]
     pub fn split_mut(this: &mut Self) -> SplitMut<'_, B> {
+        Self::finish_pending_swap(this);
+
         unsafe {
             let inner = &*this.inner;
-            let which = inner.which.load_unsync();
-            let reader = inner.buffers.read_buffer(which);
-            let writer = inner.buffers.write_buffer(which);
+            let reader = inner.buffers.get_raw(Self::published_index(this));
+            let writer = inner.buffers.get_raw(Self::write_index(this));
 
             SplitMut {
                 read: &*reader,
@@ -237,6 +437,57 @@ impl<B: BufferRef> Writer<B> {
         }
     }
 
+    // the new write buffer holds the state from two swaps ago, so bring it up to date with the published buffer
+    pub fn swap_and_sync(this: &mut Self)
+    where
+        B::Buffer: Clone,
+    {
+        Self::swap_and_sync_with(this, |published, write| write.clone_from(published))
+    }
+
+    pub fn swap_and_sync_with<F: FnOnce(&B::Buffer, &mut B::Buffer)>(this: &mut Self, f: F) {
+        Self::swap_buffers(this);
+        let split = Self::split_mut(this);
+        f(split.read, split.write)
+    }
+
+    // never waits for readers. A reader may start between the check and the swap,
+    // then the buffers are published but the swap is left pending
+    pub fn try_swap_buffers(this: &mut Self) -> TrySwap {
+        if !Self::try_finish_pending_swap(this) || this.inner.strategy.has_active_readers(&mut this.tag) {
+            return TrySwap::NotSwapped
+        }
+
+        let mut swap = match unsafe { Self::try_start_buffer_swap(this) } {
+            Ok(swap) => swap,
+            Err(_) => return TrySwap::NotSwapped,
+        };
+
+        if this.inner.strategy.is_swap_completed(&mut swap) {
+            Self::swap_finished(this, swap.start);
+            TrySwap::Completed
+        } else {
+            this.swap = Some(swap);
+            TrySwap::Pending
+        }
+    }
+
+    // the swap that the next access to the write buffer has to wait for, if there is one
+    pub fn pending_swap(this: &mut Self) -> Option<PendingSwap<'_, B>> {
+        if this.swap.is_some() {
+            Some(PendingSwap { writer: this })
+        } else {
+            None
+        }
+    }
+
+    pub fn swap_buffers_with_policy<P: WaitPolicy>(this: &mut Self, policy: P) {
+        unsafe {
+            let swap = Self::start_buffer_swap(this);
+            Self::finish_swap_with_policy(this, swap, policy);
+        }
+    }
+
     pub fn swap_buffers_with<F: FnMut(&Self)>(this: &mut Self, mut f: F) {
         let swap = unsafe { Self::start_buffer_swap(this) };
         let this: &Self = this;
@@ -244,47 +495,245 @@ impl<B: BufferRef> Writer<B> {
         Self::finish_swap_with(this, swap, f);
     }
 
-    pub unsafe fn swap_buffers_unchecked(this: &mut Self) { this.inner.which.fetch_xor(true, Ordering::Release); }
+    /// # Safety
+    ///
+    /// This doesn't wait for readers, so the caller must make sure that none of them are
+    /// still reading the new write buffer before writing to it.
+    pub unsafe fn swap_buffers_unchecked(this: &mut Self) {
+        Self::flip(this);
+        #[cfg(feature = "alloc")]
+        this.inner.notify.notify();
+    }
+
+    #[inline]
+    unsafe fn flip(this: &mut Self) {
+        let version = this.inner.which.load_unsync();
+        let next = next_version::<B::Count>(version);
+        this.inner.which.fetch_add(next.wrapping_sub(version), Ordering::Release);
+    }
 
+    /// # Safety
+    ///
+    /// The swap must be finished before the write buffer is written to.
     #[inline]
     pub unsafe fn start_buffer_swap(this: &mut Self) -> Swap<B> {
-        match Self::try_start_buffer_swap(this) {
+        Self::finish_pending_swap(this);
+
+        match Self::start_swap(this) {
             Ok(swap) => swap,
             Err(ref error) => swap_buffers_fail(error),
         }
     }
 
+    /// # Safety
+    ///
+    /// The swap must be finished before the write buffer is written to.
     #[inline]
-    pub unsafe fn try_start_buffer_swap(this: &mut Self) -> Result<Swap<B>, CaptureError<B>> {
+    pub unsafe fn try_start_buffer_swap(this: &mut Self) -> Result<Swap<B>, StartSwapError<CaptureError<B>>> {
+        if !Self::try_finish_pending_swap(this) {
+            return Err(StartSwapError::Pending)
+        }
+
+        Self::start_swap(this).map_err(StartSwapError::Capture)
+    }
+
+    // strategies that count readers per buffer can't tell the readers the pending swap is
+    // waiting on apart from the readers of the next swap, so it has to complete first
+    unsafe fn start_swap(this: &mut Self) -> Result<Swap<B>, CaptureError<B>> {
+        debug_assert!(this.swap.is_none());
         let capture = this.inner.strategy.try_capture_readers(&mut this.tag)?;
-        Self::swap_buffers(this);
+        Self::flip(this);
         let capture = this.inner.strategy.finish_capture_readers(&mut this.tag, capture);
 
-        Ok(Swap { capture })
+        // strategies that pick the buffers themselves publish the new buffer while capturing
+        // readers, so readers are only woken up once they can find it
+        #[cfg(feature = "alloc")]
+        this.inner.notify.notify();
+
+        #[cfg(feature = "metrics")]
+        this.inner.metrics.swap_started(this.inner.strategy.captured_readers(&capture));
+
+        Ok(Swap {
+            capture,
+            start: SwapStart {
+                #[cfg(feature = "metrics")]
+                time: std::time::Instant::now(),
+            },
+        })
     }
 
-    pub fn finish_swap(this: &Self, swap: Swap<B>) {
-        let mut on_drop = FinishSwapOnDrop {
-            strategy: &this.inner.strategy,
-            swap,
-            backoff: crossbeam_utils::Backoff::new(),
-        };
-        let FinishSwapOnDrop {
-            strategy: _,
-            swap,
-            backoff,
-        } = &mut on_drop;
+    // the time is measured from the moment the buffers were swapped, to the moment
+    // the writer noticed that the last captured reader exited
+    #[inline]
+    fn swap_finished(this: &Self, start: SwapStart) {
+        #[cfg(feature = "metrics")]
+        this.inner.metrics.swap_finished(start.time.elapsed());
+
+        #[cfg(not(feature = "metrics"))]
+        let _ = (this, start);
+    }
+
+    // if an earlier swap is still pending, the buffers are only swapped once the future has waited for it
+    pub fn swap_buffers_async(this: &mut Self) -> FinishSwap<'_, B> {
+        let start = this.swap.is_some();
+
+        if !start {
+            this.swap = Some(unsafe { Self::start_buffer_swap(this) });
+        }
+
+        FinishSwap { writer: this, start }
+    }
+
+    pub fn begin_swap(this: &mut Self) -> PendingSwap<'_, B> {
+        let swap = unsafe { Self::start_buffer_swap(this) };
+        this.swap = Some(swap);
+        PendingSwap { writer: this }
+    }
+
+    pub fn finish_swap_async(this: &mut Self, swap: Swap<B>) -> FinishSwap<'_, B> {
+        Self::finish_pending_swap(this);
+        this.swap = Some(swap);
+        FinishSwap { writer: this, start: false }
+    }
+
+    #[inline]
+    fn finish_pending_swap(this: &mut Self) {
+        #[cold]
+        #[inline(never)]
+        fn finish_pending_swap<B: BufferRef>(this: &mut Writer<B>) {
+            if let Some(swap) = this.swap.take() {
+                Writer::finish_swap(this, swap)
+            }
+        }
+
+        if this.swap.is_some() {
+            finish_pending_swap(this)
+        }
+    }
+
+    // returns true if there is no pending swap left
+    fn try_finish_pending_swap(this: &mut Self) -> bool {
+        match this.swap.take() {
+            Some(swap) => match Self::try_finish_swap(this, swap) {
+                Ok(()) => true,
+                Err(swap) => {
+                    this.swap = Some(swap);
+                    false
+                }
+            },
+            None => true,
+        }
+    }
 
+    // custom wait policies may drop guards while waiting, so only swaps that
+    // use the default policy check for deadlocks
+    pub fn finish_swap(this: &Self, mut swap: Swap<B>) {
+        Self::detect_deadlock(this, &mut swap);
+
+        #[cfg(feature = "std")]
+        if let Some((threshold, ref report)) = this.watchdog {
+            return Self::finish_swap_watched(this, swap, threshold, report)
+        }
+
+        Self::finish_swap_with_policy(this, swap, SpinThenPark::default())
+    }
+
+    #[cfg(feature = "std")]
+    #[cold]
+    fn finish_swap_watched(this: &Self, swap: Swap<B>, threshold: core::time::Duration, report: &Report) {
+        if let Err(swap) = Self::finish_swap_timeout(this, swap, threshold) {
+            report(&Self::blocking_readers(this, &swap));
+            Self::finish_swap_with_policy(this, swap, SpinThenPark::default())
+        }
+    }
+
+    // calls `report` with the readers holding up each swap that takes longer than `threshold` to finish.
+    // Strategies that can't identify their readers report none
+    #[cfg(feature = "std")]
+    pub fn set_watchdog<F>(this: &mut Self, threshold: core::time::Duration, report: F)
+    where
+        F: Fn(&[crate::diagnostics::ReaderInfo]) + Send + Sync + 'static,
+    {
+        this.watchdog = Some((threshold, std::boxed::Box::new(report)))
+    }
+
+    #[cfg(feature = "std")]
+    pub fn clear_watchdog(this: &mut Self) { this.watchdog = None }
+
+    #[inline]
+    fn detect_deadlock(this: &Self, swap: &mut Swap<B>) {
+        #[cfg(feature = "detect-deadlock")]
+        if !this.inner.strategy.is_swap_completed(swap) {
+            let read = Self::published_index(this);
+            let write = Self::write_index(this);
+
+            // strategies that count readers per buffer only wait on the buffer the writer moves into,
+            // the others wait on every reader that started before the swap
+            crate::deadlock::check(&*this.inner as *const _ as *const () as usize, |index| {
+                match <B::Strategy as Strategy>::BUFFER_COUNT {
+                    Some(_) => index == write,
+                    None => index != read,
+                }
+            })
+        }
+
+        #[cfg(not(feature = "detect-deadlock"))]
+        let _ = (this, swap);
+    }
+
+    pub fn finish_swap_with_policy<P: WaitPolicy>(this: &Self, swap: Swap<B>, mut policy: P) {
+        fn finish_swap_with_policy<B: BufferRef>(strategy: &B::Strategy, swap: Swap<B>, policy: &mut dyn WaitPolicy) {
+            let mut on_drop = FinishSwapOnDrop {
+                strategy,
+                swap,
+                policy: SpinThenPark::default(),
+            };
+            let swap = &mut on_drop.swap;
+
+            while !strategy.is_swap_completed(swap) {
+                wait(strategy, swap, policy, None)
+            }
+
+            core::mem::forget(on_drop)
+        }
+
+        let start = swap.start;
+        finish_swap_with_policy(&this.inner.strategy, swap, &mut policy);
+        Self::swap_finished(this, start)
+    }
+
+    pub fn try_finish_swap(this: &Self, mut swap: Swap<B>) -> Result<(), Swap<B>> {
+        if this.inner.strategy.is_swap_completed(&mut swap) {
+            Self::swap_finished(this, swap.start);
+            Ok(())
+        } else {
+            Err(swap)
+        }
+    }
+
+    #[cfg(feature = "std")]
+    pub fn finish_swap_timeout(this: &Self, mut swap: Swap<B>, timeout: std::time::Duration) -> Result<(), Swap<B>> {
         let strategy = &this.inner.strategy;
+        let mut policy = SpinThenPark::default();
+        let deadline = std::time::Instant::now().checked_add(timeout);
+
+        while !strategy.is_swap_completed(&mut swap) {
+            let timeout = match deadline {
+                Some(deadline) => match deadline.checked_duration_since(std::time::Instant::now()) {
+                    Some(timeout) if !timeout.is_zero() => Some(timeout),
+                    _ => return Err(swap),
+                },
+                None => None,
+            };
 
-        while !strategy.is_swap_completed(swap) {
-            snooze(&backoff)
+            wait(strategy, &mut swap, &mut policy, timeout)
         }
 
-        core::mem::forget(on_drop);
+        Self::swap_finished(this, swap.start);
+        Ok(())
     }
 
-    pub fn finish_swap_with<F: FnMut()>(this: &Self, swap: Swap<B>, ref mut f: F) {
+    pub fn finish_swap_with<F: FnMut()>(this: &Self, swap: Swap<B>, mut f: F) {
         #[cold]
         #[inline(never)]
         fn cold(f: &mut dyn FnMut()) { f() }
@@ -293,7 +742,7 @@ impl<B: BufferRef> Writer<B> {
             let mut on_drop = FinishSwapOnDrop {
                 strategy,
                 swap,
-                backoff: crossbeam_utils::Backoff::new(),
+                policy: SpinThenPark::default(),
             };
             let swap = &mut on_drop.swap;
 
@@ -304,18 +753,53 @@ impl<B: BufferRef> Writer<B> {
             core::mem::forget(on_drop)
         }
 
-        finish_swap_with(&this.inner.strategy, swap, f)
+        let start = swap.start;
+        finish_swap_with(&this.inner.strategy, swap, &mut f);
+        Self::swap_finished(this, start)
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B: BufferRef> Writer<B> {
+    // readers that are currently holding a guard
+    pub fn active_readers(this: &Self) -> std::vec::Vec<crate::diagnostics::ReaderInfo> {
+        this.inner.strategy.active_readers()
+    }
+
+    // readers that the swap is still waiting on
+    pub fn blocking_readers(this: &Self, swap: &Swap<B>) -> std::vec::Vec<crate::diagnostics::ReaderInfo> {
+        this.inner.strategy.blocking_readers(&swap.capture)
     }
 }
 
+#[cfg(feature = "metrics")]
+impl<B: BufferRef> Writer<B> {
+    pub fn stats(this: &Self) -> crate::metrics::SwapStats { this.inner.metrics.stats() }
+
+    // the hook is shared by the writer and all readers, and can only be set once
+    pub fn set_metrics_hook<H: crate::metrics::MetricsHook + 'static>(
+        this: &Self,
+        hook: H,
+    ) -> Result<(), std::boxed::Box<dyn crate::metrics::MetricsHook>> {
+        this.inner.metrics.set_hook(std::boxed::Box::new(hook))
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B: BufferRef> Drop for Writer<B> {
+    fn drop(&mut self) { self.inner.notify.close() }
+}
+
 impl<B: BufferRef> Reader<B> {
     #[inline]
+    #[track_caller]
     pub fn try_clone(&self) -> Result<Self, B::UpgradeError> {
-        let inner = B::upgrade(&self.inner)?;
-        let tag = unsafe { inner.strategy.reader_tag() };
+        let inner = B::upgrade_ref(&self.inner)?;
+        let tag = reader_tag::<B>(&inner.strategy);
         Ok(Reader {
             inner: self.inner.clone(),
             tag,
+            last_seen: self.last_seen,
         })
     }
 
@@ -327,30 +811,149 @@ impl<B: BufferRef> Reader<B> {
 
     #[inline]
     pub fn try_get(&mut self) -> Result<ReaderGuard<'_, B>, B::UpgradeError> {
-        let keep_alive = B::upgrade(&self.inner)?;
-        let inner = &*keep_alive;
-        let guard = inner.strategy.begin_guard(&mut self.tag);
+        let inner = B::upgrade_ref(&self.inner)?;
+        let guard = Self::get_with(inner, &mut self.tag);
+        self.last_seen = ReaderGuard::version(&guard);
+        Ok(guard)
+    }
+
+    #[inline]
+    pub fn get_if_newer(&mut self, last_seen: usize) -> Option<ReaderGuard<'_, B>> {
+        self.try_get_if_newer(last_seen).expect("Tried to reader from a dangling `Reader<B>`")
+    }
+
+    #[inline]
+    pub fn try_get_if_newer(&mut self, last_seen: usize) -> Result<Option<ReaderGuard<'_, B>>, B::UpgradeError> {
+        let inner = B::upgrade_ref(&self.inner)?;
+
+        if inner.which.load(Ordering::Acquire) == last_seen {
+            return Ok(None)
+        }
+
+        // strategies that pick the buffers themselves may not have published the new buffer yet
+        let guard = Self::get_with(inner, &mut self.tag);
+
+        self.last_seen = ReaderGuard::version(&guard);
+
+        if self.last_seen == last_seen {
+            Ok(None)
+        } else {
+            Ok(Some(guard))
+        }
+    }
+
+    // the buffers that were published before the latest one stay readable until the writer moves
+    // back into them, so with more than two buffers a reader can keep reading an older version
+    #[inline]
+    pub fn get_version(&mut self, version: usize) -> Option<ReaderGuard<'_, B>> {
+        self.try_get_version(version).expect("Tried to reader from a dangling `Reader<B>`")
+    }
+
+    pub fn try_get_version(&mut self, version: usize) -> Result<Option<ReaderGuard<'_, B>>, B::UpgradeError> {
+        let inner = B::upgrade_ref(&self.inner)?;
+        let (guard, latest) = match inner.strategy.begin_guard_at(&mut self.tag, &inner.which, version) {
+            Some(guard) => guard,
+            None => return Ok(None),
+        };
 
-        let which = inner.which.load(Ordering::Acquire);
-        let buffer = inner.buffers.read_buffer(which);
+        // the writer writes to the buffer after the latest one, and the next swap moves it into the buffer after that
+        if inner.strategy.guard_index(&guard).is_none()
+            && version_age::<B::Count>(latest, version) > <B::Count as BufferCount>::N - 2
+        {
+            inner.strategy.end_guard(guard);
+            Ok(None)
+        } else {
+            Ok(Some(Self::guard(inner, guard, version)))
+        }
+    }
+
+    #[inline]
+    fn get_with<'reader>(inner: &'reader BufferRefData<B>, tag: &mut ReaderTag<B>) -> ReaderGuard<'reader, B> {
+        let (guard, version) = inner.strategy.begin_guard_latest(tag, &inner.which);
+        Self::guard(inner, guard, version)
+    }
+
+    #[inline]
+    fn guard<'reader>(
+        inner: &'reader BufferRefData<B>,
+        guard: <B::Strategy as Strategy>::RawGuard,
+        version: usize,
+    ) -> ReaderGuard<'reader, B> {
+        let index = match inner.strategy.guard_index(&guard) {
+            Some(index) => index,
+            None => version % <B::Count as BufferCount>::N,
+        };
 
-        Ok(ReaderGuard {
-            value: unsafe { &*buffer },
+        ReaderGuard {
+            value: unsafe { &*inner.buffers.get_raw(index) },
             raw: RawGuard {
                 raw: ManuallyDrop::new(guard),
-                keep_alive,
+                version,
+                inner,
+                #[cfg(feature = "detect-deadlock")]
+                index,
+                #[cfg(feature = "detect-deadlock")]
+                guards: crate::deadlock::acquire(inner as *const _ as *const () as usize, index),
+                #[cfg(feature = "metrics")]
+                acquired: std::time::Instant::now(),
             },
-        })
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B: BufferRef> Reader<B> {
+    pub fn set_name<N: Into<std::borrow::Cow<'static, str>>>(&mut self, name: N) {
+        if let Ok(inner) = B::upgrade_ref(&self.inner) {
+            inner.strategy.name_reader(&mut self.tag, name.into())
+        }
+    }
+
+    // resolves once there is a newer version than the last one this reader got
+    pub fn changed(&self) -> Changed<'_, B> { Changed { reader: self } }
+
+    #[cfg(feature = "std")]
+    pub fn wait_for_swap(&self) -> Result<(), Closed> { self.wait_for_swap_inner(None).map(drop) }
+
+    #[cfg(feature = "std")]
+    pub fn wait_for_swap_timeout(&self, timeout: std::time::Duration) -> Result<bool, Closed> {
+        self.wait_for_swap_inner(std::time::Instant::now().checked_add(timeout))
+    }
+
+    #[cfg(feature = "std")]
+    fn wait_for_swap_inner(&self, deadline: Option<std::time::Instant>) -> Result<bool, Closed> {
+        let inner = B::upgrade_ref(&self.inner).map_err(|_| Closed)?;
+        let version = self.last_seen;
+        let is_swapped = || inner.which.load(Ordering::SeqCst) != version;
+
+        if inner.notify.wait(|| is_swapped() || inner.notify.is_closed(), deadline) && !is_swapped() {
+            Err(Closed)
+        } else {
+            Ok(is_swapped())
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B: BufferRef<Strategy = crate::qsbr::QsbrStrategy>> Reader<B> {
+    #[inline]
+    pub fn quiescent(&mut self) {
+        if let Ok(inner) = B::upgrade_ref(&self.inner) {
+            inner.strategy.quiescent(&mut self.tag)
+        }
     }
 }
 
 impl<'a, B: BufferRef, T: ?Sized> ReaderGuard<'a, B, T> {
     #[inline]
-    pub fn raw_guard(this: &Self) -> &RawGuard<B> { &this.raw }
+    pub fn raw_guard(this: &Self) -> &RawGuard<'a, B> { &this.raw }
+
+    #[inline]
+    pub fn version(this: &Self) -> usize { this.raw.version }
 
     pub fn map<F, U: ?Sized>(this: Self, f: F) -> ReaderGuard<'a, B, U>
     where
-        F: for<'val> FnOnce(&'val T, &RawGuard<B>) -> &'val U,
+        F: for<'val> FnOnce(&'val T, &RawGuard<'a, B>) -> &'val U,
     {
         ReaderGuard {
             value: f(this.value, Self::raw_guard(&this)),
@@ -360,7 +963,7 @@ impl<'a, B: BufferRef, T: ?Sized> ReaderGuard<'a, B, T> {
 
     pub fn try_map<F, U: ?Sized>(this: Self, f: F) -> Result<ReaderGuard<'a, B, U>, Self>
     where
-        F: for<'val> FnOnce(&'val T, &RawGuard<B>) -> Option<&'val U>,
+        F: for<'val> FnOnce(&'val T, &RawGuard<'a, B>) -> Option<&'val U>,
     {
         match f(this.value, Self::raw_guard(&this)) {
             None => Err(this),
@@ -370,7 +973,7 @@ impl<'a, B: BufferRef, T: ?Sized> ReaderGuard<'a, B, T> {
 
     pub fn try_map_res<F, U: ?Sized, E>(this: Self, f: F) -> Result<ReaderGuard<'a, B, U>, (Self, E)>
     where
-        F: for<'val> FnOnce(&'val T, &RawGuard<B>) -> Result<&'val U, E>,
+        F: for<'val> FnOnce(&'val T, &RawGuard<'a, B>) -> Result<&'val U, E>,
     {
         match f(this.value, Self::raw_guard(&this)) {
             Err(e) => Err((this, e)),
@@ -379,15 +982,19 @@ impl<'a, B: BufferRef, T: ?Sized> ReaderGuard<'a, B, T> {
     }
 }
 
-impl<'a, B: BufferRef> RawGuard<B> {
+impl<B: BufferRef> RawGuard<'_, B> {
+    #[inline]
+    pub fn version(&self) -> usize { self.version }
+
     #[inline]
-    pub fn strategy(&self) -> &B::Strategy { &self.keep_alive.strategy }
+    pub fn strategy(&self) -> &B::Strategy { &self.inner.strategy }
 
     #[inline]
-    pub fn extra(&self) -> &B::Extra { &self.keep_alive.extra }
+    pub fn extra(&self) -> &B::Extra { &self.inner.extra }
 }
 
 impl<B: BufferRef<UpgradeError = core::convert::Infallible>> Clone for Reader<B> {
+    #[track_caller]
     fn clone(&self) -> Self {
         match self.try_clone() {
             Ok(reader) => reader,
@@ -400,24 +1007,87 @@ impl<B: BufferRef> Deref for Writer<B> {
     type Target = B::Buffer;
 
     #[inline]
-    fn deref(&self) -> &Self::Target {
-        unsafe {
-            let inner = &*self.inner;
-            let which = inner.which.load_unsync();
-            let write = inner.buffers.write_buffer(which);
-            &*write
-        }
-    }
+    fn deref(&self) -> &Self::Target { unsafe { &*self.inner.buffers.get_raw(Self::write_index(self)) } }
 }
 
 impl<B: BufferRef> DerefMut for Writer<B> {
     #[inline]
     fn deref_mut(&mut self) -> &mut Self::Target {
-        unsafe {
-            let inner = &*self.inner;
-            let which = inner.which.load_unsync();
-            let write = inner.buffers.write_buffer(which);
-            &mut *write
+        Self::finish_pending_swap(self);
+        unsafe { &mut *self.inner.buffers.get_raw(Self::write_index(self)) }
+    }
+}
+
+impl<B: BufferRef> Future for FinishSwap<'_, B> {
+    type Output = ();
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let FinishSwap { writer, start } = self.get_mut();
+
+        loop {
+            if let Some(ref mut swap) = writer.swap {
+                match writer.inner.strategy.poll_readers_have_exited(&mut swap.capture, ctx) {
+                    Poll::Ready(()) => {
+                        let start = swap.start;
+                        writer.swap = None;
+                        Writer::swap_finished(writer, start)
+                    }
+                    Poll::Pending => return Poll::Pending,
+                }
+            }
+
+            if !core::mem::replace(start, false) {
+                return Poll::Ready(())
+            }
+
+            writer.swap = Some(unsafe { Writer::start_buffer_swap(writer) });
+        }
+    }
+}
+
+impl<'a, B: BufferRef> PendingSwap<'a, B> {
+    #[inline]
+    pub fn read(&self) -> &B::Buffer { Writer::read(self.writer) }
+
+    #[inline]
+    pub fn extra(&self) -> &B::Extra { Writer::extra(self.writer) }
+
+    pub fn wait(self) -> &'a mut B::Buffer { DerefMut::deref_mut(self.writer) }
+
+    pub fn try_wait(self) -> Result<&'a mut B::Buffer, Self> {
+        if Writer::try_finish_pending_swap(self.writer) {
+            Ok(self.wait())
+        } else {
+            Err(self)
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<B: BufferRef> Future for Changed<'_, B> {
+    type Output = Result<(), Closed>;
+
+    fn poll(self: Pin<&mut Self>, ctx: &mut Context<'_>) -> Poll<Self::Output> {
+        let version = self.reader.last_seen;
+        let inner = match B::upgrade_ref(&self.reader.inner) {
+            Ok(inner) => inner,
+            Err(_) => return Poll::Ready(Err(Closed)),
+        };
+        let notify = &inner.notify;
+        let is_swapped = || inner.which.load(Ordering::SeqCst) != version;
+
+        if !is_swapped() && !notify.is_closed() {
+            notify.register(ctx.waker());
+
+            if !is_swapped() && !notify.is_closed() {
+                return Poll::Pending
+            }
+        }
+
+        if is_swapped() {
+            Poll::Ready(Ok(()))
+        } else {
+            Poll::Ready(Err(Closed))
         }
     }
 }
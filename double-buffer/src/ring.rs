@@ -0,0 +1,248 @@
+use core::task::{Context, Poll};
+
+use crate::{
+    loom::atomic::{AtomicUsize, Ordering},
+    raw::next_version,
+    side::too_many_guards,
+    waker::AtomicWaker,
+    Count, Strategy,
+};
+use crossbeam_utils::CachePadded;
+
+pub type BufferData<B, const N: usize, E = ()> = crate::BufferData<AtomicUsize, RingStrategy<N>, B, E, Count<N>>;
+
+// marks a buffer that readers may not start on, because the writer owns it or it was never published
+const UNPUBLISHED: usize = usize::MAX;
+
+// every buffer remembers the version that was published in it, and readers are counted on the buffer
+// that they are reading. After publishing a buffer the writer claims the oldest buffer that nobody is
+// reading, so readers can keep older versions pinned while the writer rotates through the remaining
+// buffers. The writer only waits if readers hold every buffer except the latest one
+pub struct RingStrategy<const N: usize> {
+    versions: [AtomicUsize; N],
+    readers: [CachePadded<AtomicUsize>; N],
+    latest: CachePadded<AtomicUsize>,
+    waker: AtomicWaker,
+}
+
+pub struct RawGuard(usize);
+
+// the buffer that the writer moves into, if readers were still reading it
+pub struct Capture(Option<usize>);
+
+pub struct ReaderTag(());
+pub struct WriterTag {
+    version: usize,
+    published: usize,
+    write: usize,
+}
+
+impl<const N: usize> Default for RingStrategy<N> {
+    fn default() -> Self {
+        assert!(N >= 3, "A buffer ring needs at least three buffers");
+
+        Self {
+            versions: core::array::from_fn(|index| AtomicUsize::new(if index == 0 { 0 } else { UNPUBLISHED })),
+            readers: core::array::from_fn(|_| CachePadded::new(AtomicUsize::new(0))),
+            latest: CachePadded::new(AtomicUsize::new(0)),
+            waker: AtomicWaker::default(),
+        }
+    }
+}
+
+impl<const N: usize> RingStrategy<N> {
+    fn pin(&self, index: usize, version: usize) -> Option<RawGuard> {
+        if version == UNPUBLISHED {
+            return None
+        }
+
+        if self.readers[index].fetch_add(1, Ordering::SeqCst) > isize::MAX as usize {
+            too_many_guards(isize::MAX as usize)
+        }
+
+        // the writer may have claimed the buffer before we registered
+        if self.versions[index].load(Ordering::SeqCst) == version {
+            Some(RawGuard(index))
+        } else {
+            self.end_guard(RawGuard(index));
+            None
+        }
+    }
+
+    fn pin_latest(&self) -> (RawGuard, usize) {
+        loop {
+            let index = self.latest.load(Ordering::SeqCst);
+            let version = self.versions[index].load(Ordering::SeqCst);
+
+            // the writer only claims a buffer after publishing a newer one, so this retries at most once per swap
+            if let Some(guard) = self.pin(index, version) {
+                return (guard, version)
+            }
+        }
+    }
+
+    // the buffers other than `latest`, oldest first
+    fn spares(&self, latest: usize, version: usize) -> impl Iterator<Item = usize> {
+        let age = |index: usize| match self.versions[index].load(Ordering::Relaxed) {
+            UNPUBLISHED => usize::MAX,
+            other => version.wrapping_sub(other),
+        };
+
+        let mut spares = [0; N];
+        let mut len = 0;
+
+        for index in (0..N).filter(|&index| index != latest) {
+            spares[len] = index;
+            len += 1;
+        }
+
+        spares[..len].sort_unstable_by_key(|&index| core::cmp::Reverse(age(index)));
+        IntoIterator::into_iter(spares).take(len)
+    }
+
+    // readers only start on the latest buffer, or on a buffer that still holds the version they
+    // ask for, so the writer can take any other buffer as soon as its readers are gone. Returns
+    // the buffer and whether the writer has to wait for its readers
+    fn claim(&self, tag: &WriterTag) -> (usize, bool) {
+        let mut oldest = None;
+
+        for index in self.spares(tag.published, tag.version) {
+            oldest = oldest.or(Some(index));
+
+            if self.readers[index].load(Ordering::SeqCst) != 0 {
+                continue
+            }
+
+            let version = self.versions[index].swap(UNPUBLISHED, Ordering::SeqCst);
+
+            // a read-modify-write always sees the latest count, so either this sees the readers
+            // that entered the buffer, or they see that it was claimed and leave it again
+            if self.readers[index].fetch_add(0, Ordering::SeqCst) == 0 {
+                return (index, false)
+            }
+
+            self.versions[index].store(version, Ordering::SeqCst);
+        }
+
+        // readers hold every spare buffer, so the writer has to wait for the oldest one
+        let index = oldest.expect("a buffer ring has at least one spare buffer");
+        self.versions[index].store(UNPUBLISHED, Ordering::SeqCst);
+        self.readers[index].fetch_add(0, Ordering::SeqCst);
+
+        (index, true)
+    }
+}
+
+unsafe impl<const N: usize> Strategy for RingStrategy<N> {
+    type Whitch = AtomicUsize;
+    type ReaderTag = ReaderTag;
+    type WriterTag = WriterTag;
+    type RawGuard = RawGuard;
+
+    type FastCapture = ();
+    type CaptureError = core::convert::Infallible;
+    type Capture = Capture;
+
+    const BUFFER_COUNT: Option<usize> = Some(N);
+    const READERS_EXIT_IN_END_GUARD: bool = true;
+
+    #[inline]
+    unsafe fn reader_tag(&self) -> Self::ReaderTag { ReaderTag(()) }
+
+    // `raw::new` publishes version 0 again, so the buffers start over from the first one
+    unsafe fn writer_tag(&self) -> Self::WriterTag {
+        for (index, version) in self.versions.iter().enumerate() {
+            version.store(if index == 0 { 0 } else { UNPUBLISHED }, Ordering::Relaxed);
+        }
+
+        self.latest.store(0, Ordering::Relaxed);
+
+        WriterTag {
+            version: 0,
+            published: 0,
+            write: 1,
+        }
+    }
+
+    // the buffer that the writer publishes next becomes the latest one
+    fn has_active_readers(&self, tag: &mut Self::WriterTag) -> bool {
+        self.spares(tag.write, tag.version).all(|index| self.readers[index].load(Ordering::SeqCst) != 0)
+    }
+
+    #[inline]
+    fn try_capture_readers(&self, _: &mut Self::WriterTag) -> Result<Self::FastCapture, Self::CaptureError> { Ok(()) }
+
+    // the previously published buffer can only be claimed once readers can't start on it anymore
+    fn finish_capture_readers(&self, tag: &mut Self::WriterTag, (): Self::FastCapture) -> Self::Capture {
+        tag.version = next_version::<Count<N>>(tag.version);
+        self.versions[tag.write].store(tag.version, Ordering::SeqCst);
+        self.latest.store(tag.write, Ordering::SeqCst);
+        tag.published = tag.write;
+
+        let (index, is_pinned) = self.claim(tag);
+        tag.write = index;
+
+        Capture(if is_pinned { Some(index) } else { None })
+    }
+
+    #[inline]
+    fn readers_have_exited(&self, capture: &mut Self::Capture) -> bool {
+        match capture.0 {
+            Some(index) => self.readers[index].load(Ordering::Acquire) == 0,
+            None => true,
+        }
+    }
+
+    fn poll_readers_have_exited(&self, capture: &mut Self::Capture, ctx: &mut Context<'_>) -> Poll<()> {
+        let index = match capture.0 {
+            Some(index) if self.readers[index].load(Ordering::Acquire) != 0 => index,
+            _ => return Poll::Ready(()),
+        };
+
+        self.waker.register(ctx.waker());
+
+        if self.readers[index].load(Ordering::SeqCst) == 0 {
+            Poll::Ready(())
+        } else {
+            Poll::Pending
+        }
+    }
+
+    #[inline]
+    fn begin_guard(&self, _: &mut Self::ReaderTag) -> Self::RawGuard { self.pin_latest().0 }
+
+    #[inline]
+    fn begin_guard_latest(&self, _: &mut Self::ReaderTag, _: &Self::Whitch) -> (Self::RawGuard, usize) {
+        self.pin_latest()
+    }
+
+    fn begin_guard_at(&self, _: &mut Self::ReaderTag, which: &Self::Whitch, version: usize) -> Option<(Self::RawGuard, usize)> {
+        let index = (0..N).find(|&index| self.versions[index].load(Ordering::SeqCst) == version)?;
+        let guard = self.pin(index, version)?;
+        Some((guard, which.load(Ordering::Acquire)))
+    }
+
+    #[inline]
+    fn write_index(&self, tag: &Self::WriterTag) -> Option<usize> { Some(tag.write) }
+
+    #[inline]
+    fn published_index(&self, tag: &Self::WriterTag) -> Option<usize> { Some(tag.published) }
+
+    #[inline]
+    fn guard_index(&self, &RawGuard(index): &Self::RawGuard) -> Option<usize> { Some(index) }
+
+    #[inline]
+    fn end_guard(&self, RawGuard(index): Self::RawGuard) {
+        if self.readers[index].fetch_sub(1, Ordering::SeqCst) == 1 && self.waker.is_registered() {
+            self.waker.wake()
+        }
+    }
+
+    #[cfg(feature = "metrics")]
+    fn captured_readers(&self, capture: &Self::Capture) -> usize {
+        match capture.0 {
+            Some(index) => self.readers[index].load(Ordering::Relaxed),
+            None => 0,
+        }
+    }
+}
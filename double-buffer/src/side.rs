@@ -0,0 +1,53 @@
+use crate::loom::atomic::{AtomicUsize, Ordering};
+
+// readers are counted on the side that was current when they started, and a swap flips sides,
+// so that the writer only waits for the readers that started before it
+#[derive(Default)]
+pub(crate) struct Side(AtomicUsize);
+
+impl Side {
+    // registers a reader on the current side and returns that side. `enter` adds the reader to the
+    // count of a side and returns the count before, and `exit` removes it again
+    #[inline]
+    pub fn enter<E, X>(&self, max: usize, mut enter: E, mut exit: X) -> usize
+    where
+        E: FnMut(usize) -> usize,
+        X: FnMut(usize),
+    {
+        loop {
+            let side = self.0.load(Ordering::SeqCst);
+
+            if enter(side) > max {
+                too_many_guards(max)
+            }
+
+            // the writer flipped sides before we registered, so it may not wait for us
+            if self.0.load(Ordering::SeqCst) == side {
+                return side
+            }
+
+            exit(side)
+        }
+    }
+
+    // returns the side that the writer has to wait for. Its count must then be read with a
+    // read-modify-write, which always sees the latest count, so either the writer sees the
+    // readers that entered the old side, or they see the new side and move over to it
+    #[inline]
+    pub fn flip(&self) -> usize { self.0.fetch_xor(1, Ordering::SeqCst) }
+}
+
+#[cold]
+#[inline(never)]
+pub(crate) fn too_many_guards(max: usize) -> ! {
+    struct Abort;
+
+    impl Drop for Abort {
+        fn drop(&mut self) { panic!() }
+    }
+
+    // double panic = abort
+    let _abort = Abort;
+
+    panic!("Tried to create more than {} guards!", max)
+}
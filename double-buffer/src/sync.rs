@@ -1,16 +1,32 @@
-use crate::{thin::Arc, Strategy};
-use core::sync::atomic::{AtomicBool, AtomicU32, Ordering};
+use crate::{
+    diagnostics::ReaderInfo,
+    loom::{
+        atomic::{self, AtomicU32, AtomicUsize, Ordering},
+        Mutex,
+    },
+    thin::Arc,
+    waker::AtomicWaker,
+    Strategy,
+};
+use core::{
+    ptr::NonNull,
+    task::{Context, Poll},
+};
 use smallvec::SmallVec;
+use std::{borrow::Cow, vec::Vec};
 
-#[cfg(feature = "std")]
-use parking_lot::Mutex;
-#[cfg(not(feature = "std"))]
-use spin::Mutex;
-
+#[cfg(all(feature = "std", target_os = "linux", not(loom)))]
+pub mod futex;
 #[cfg(feature = "std")]
 pub mod park;
+mod registry;
 
-pub type BufferData<B, E = ()> = crate::BufferData<AtomicBool, SyncStrategy, B, E>;
+pub type BufferData<B, E = ()> = crate::BufferData<AtomicUsize, SyncStrategy, B, E>;
+
+// a reader tag is set to `ACTIVE` while its reader holds a guard, and a waiting
+// writer sets `WAITING` so that the reader knows to wake it once it's done
+const ACTIVE: u32 = 0b01;
+const WAITING: u32 = 0b10;
 
 crate::__imp_make_newtype! {
     crate::sync::SyncStrategy, core::convert::Infallible, ArcInner, std::sync::Arc
@@ -18,22 +34,95 @@ crate::__imp_make_newtype! {
 
 #[derive(Default)]
 pub struct SyncStrategy {
-    tag_list: Mutex<SmallVec<[Arc<AtomicU32>; 8]>>,
+    tag_list: registry::Registry,
+    waker: AtomicWaker,
+}
+
+struct Tag {
+    state: AtomicU32,
+    info: Mutex<Option<ReaderInfo>>,
+    index: usize,
+    // the next tag on the registry's free list, see `Registry::claim`
+    next_free: AtomicUsize,
 }
 
 pub struct RawGuard {
-    tag: Arc<AtomicU32>,
+    // tags are only freed once the strategy is dropped, so this stays valid
+    // for as long as the guard can be passed to `end_guard`
+    tag: NonNull<AtomicU32>,
 }
 
+unsafe impl Send for RawGuard {}
+unsafe impl Sync for RawGuard {}
+
 pub struct Capture {
-    active: SmallVec<[Arc<AtomicU32>; 8]>,
+    active: SmallVec<[Arc<Tag>; 8]>,
 }
 
-pub struct ReaderTag(Arc<AtomicU32>);
+// tags point back at the registry to put themselves on its free list, so they have to be dropped before the strategy
+pub struct ReaderTag(Arc<Tag>, NonNull<registry::Registry>);
 pub struct WriterTag(());
 
+unsafe impl Send for ReaderTag {}
+unsafe impl Sync for ReaderTag {}
+
+impl Drop for ReaderTag {
+    fn drop(&mut self) { unsafe { self.1.as_ref() }.release(&self.0) }
+}
+
+impl Tag {
+    fn new(index: usize) -> Self {
+        Self {
+            state: AtomicU32::new(0),
+            info: Mutex::new(None),
+            index,
+            next_free: AtomicUsize::new(0),
+        }
+    }
+}
+
+impl Capture {
+    // it's enough to wait on any one reader, once it exits we will check the others again
+    fn mark_waiting(&self) -> bool {
+        self.active.iter().any(|tag| {
+            tag.state
+                .fetch_update(Ordering::SeqCst, Ordering::Relaxed, |tag| {
+                    if tag & ACTIVE != 0 {
+                        Some(tag | WAITING)
+                    } else {
+                        None
+                    }
+                })
+                .is_ok()
+        })
+    }
+}
+
+impl SyncStrategy {
+    // returns true if a writer is waiting on this guard
+    #[inline]
+    fn release(&self, guard: RawGuard) -> bool {
+        let tag = unsafe { guard.tag.as_ref() }.fetch_and(!(ACTIVE | WAITING), Ordering::SeqCst);
+        let is_waiting = tag & WAITING != 0;
+
+        if is_waiting {
+            self.wake()
+        }
+
+        is_waiting
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn wake(&self) {
+        if self.waker.is_registered() {
+            self.waker.wake()
+        }
+    }
+}
+
 unsafe impl Strategy for SyncStrategy {
-    type Whitch = AtomicBool;
+    type Whitch = AtomicUsize;
     type ReaderTag = ReaderTag;
     type WriterTag = WriterTag;
     type RawGuard = RawGuard;
@@ -42,58 +131,125 @@ unsafe impl Strategy for SyncStrategy {
     type CaptureError = core::convert::Infallible;
     type Capture = Capture;
 
+    const READERS_EXIT_IN_END_GUARD: bool = true;
+
     #[inline]
     unsafe fn reader_tag(&self) -> Self::ReaderTag {
-        let tag = Arc::new(AtomicU32::new(0));
-        self.tag_list.lock().push(tag.clone());
-        ReaderTag(tag)
+        let tag = match self.tag_list.claim() {
+            Some(tag) => {
+                *tag.info.lock() = None;
+                tag
+            }
+            None => self.tag_list.insert(),
+        };
+
+        atomic::fence(Ordering::SeqCst);
+        ReaderTag(tag, NonNull::from(&self.tag_list))
     }
 
     #[inline]
     unsafe fn writer_tag(&self) -> Self::WriterTag { WriterTag(()) }
 
+    fn has_active_readers(&self, _: &mut Self::WriterTag) -> bool {
+        atomic::fence(Ordering::SeqCst);
+
+        self.tag_list
+            .tags()
+            .any(|tag| Arc::strong_count(&tag) != 1 && tag.state.load(Ordering::Acquire) & ACTIVE != 0)
+    }
+
     #[inline]
     fn try_capture_readers(&self, _: &mut Self::WriterTag) -> Result<Self::FastCapture, Self::CaptureError> {
-        core::sync::atomic::fence(Ordering::SeqCst);
+        atomic::fence(Ordering::SeqCst);
         Ok(())
     }
 
     #[inline]
     fn finish_capture_readers(&self, _: &mut Self::WriterTag, (): Self::FastCapture) -> Self::Capture {
-        let mut active = SmallVec::new();
-
-        self.tag_list.lock().retain(|tag| {
-            let is_alive = Arc::strong_count(tag) != 1;
-
-            if is_alive && tag.load(Ordering::Acquire) & 1 == 1 {
-                active.push(tag.clone())
-            }
-
-            is_alive
-        });
+        // pairs with the fence in `reader_tag`, so that new readers are either seen here or see the new buffer
+        atomic::fence(Ordering::SeqCst);
+
+        let active = self
+            .tag_list
+            .tags()
+            // a read-modify-write always sees the latest state, so either this sees that the reader
+            // is active, or the reader sees the new buffer once it becomes active
+            .filter(|tag| tag.state.fetch_or(0, Ordering::SeqCst) & ACTIVE != 0)
+            .map(|tag| Arc::clone(&tag))
+            .collect();
 
         Capture { active }
     }
 
     #[inline]
     fn readers_have_exited(&self, capture: &mut Self::Capture) -> bool {
-        capture.active.retain(|tag| tag.load(Ordering::Relaxed) & 1 == 1);
+        capture.active.retain(|tag| tag.state.load(Ordering::Relaxed) & ACTIVE != 0);
 
         let readers_have_exited = capture.active.is_empty();
 
         if readers_have_exited {
-            core::sync::atomic::fence(Ordering::SeqCst);
+            atomic::fence(Ordering::SeqCst);
         }
 
         readers_have_exited
     }
 
+    fn poll_readers_have_exited(&self, capture: &mut Self::Capture, ctx: &mut Context<'_>) -> Poll<()> {
+        if self.readers_have_exited(capture) {
+            return Poll::Ready(())
+        }
+
+        self.waker.register(ctx.waker());
+
+        loop {
+            if capture.mark_waiting() {
+                return Poll::Pending
+            }
+
+            if self.readers_have_exited(capture) {
+                return Poll::Ready(())
+            }
+        }
+    }
+
     #[inline]
     fn begin_guard(&self, tag: &mut Self::ReaderTag) -> Self::RawGuard {
-        tag.0.fetch_add(1, Ordering::Acquire);
-        RawGuard { tag: tag.0.clone() }
+        tag.0.state.fetch_or(ACTIVE, Ordering::Acquire);
+        RawGuard {
+            tag: NonNull::from(&tag.0.state),
+        }
     }
 
     #[inline]
-    fn end_guard(&self, guard: Self::RawGuard) { guard.tag.fetch_add(1, Ordering::Acquire); }
+    fn end_guard(&self, guard: Self::RawGuard) { self.release(guard); }
+
+    fn describe_reader<F: FnOnce() -> ReaderInfo>(&self, tag: &mut Self::ReaderTag, info: F) {
+        *tag.0.info.lock() = Some(info())
+    }
+
+    fn name_reader(&self, tag: &mut Self::ReaderTag, name: Cow<'static, str>) {
+        if let Some(ref mut info) = *tag.0.info.lock() {
+            info.set_name(name)
+        }
+    }
+
+    fn active_readers(&self) -> Vec<ReaderInfo> {
+        self.tag_list
+            .tags()
+            .filter(|tag| Arc::strong_count(tag) != 1 && tag.state.load(Ordering::Acquire) & ACTIVE != 0)
+            .filter_map(|tag| tag.info.lock().clone())
+            .collect()
+    }
+
+    fn blocking_readers(&self, capture: &Self::Capture) -> Vec<ReaderInfo> {
+        capture
+            .active
+            .iter()
+            .filter(|tag| tag.state.load(Ordering::Acquire) & ACTIVE != 0)
+            .filter_map(|tag| tag.info.lock().clone())
+            .collect()
+    }
+
+    #[cfg(feature = "metrics")]
+    fn captured_readers(&self, capture: &Self::Capture) -> usize { capture.active.len() }
 }
@@ -0,0 +1,165 @@
+use crate::{side::Side, waker::AtomicWaker, Strategy};
+use core::{
+    sync::atomic::{AtomicU32, AtomicUsize, Ordering},
+    task::{Context, Poll},
+    time::Duration,
+};
+
+pub type BufferData<B, E = ()> = crate::BufferData<AtomicUsize, FutexStrategy, B, E>;
+
+crate::__imp_make_newtype! {
+    crate::sync::futex::FutexStrategy, core::convert::Infallible, ArcInner, std::sync::Arc
+}
+
+// like `AtomicStrategy`, but the writer sets `WAITING` on the side it is waiting for,
+// so that the last reader to leave that side knows to wake it
+const WAITING: u32 = 1 << 31;
+const COUNT: u32 = !WAITING;
+
+#[derive(Default)]
+pub struct FutexStrategy {
+    side: Side,
+    counts: [AtomicU32; 2],
+    waker: AtomicWaker,
+}
+
+pub struct RawGuard(usize);
+pub struct Capture(usize);
+
+pub struct ReaderTag(());
+pub struct WriterTag(());
+
+fn futex_wait(futex: &AtomicU32, expected: u32, timeout: Option<Duration>) {
+    let timeout = timeout.map(|timeout| libc::timespec {
+        tv_sec: timeout.as_secs().min(libc::time_t::MAX as u64) as libc::time_t,
+        tv_nsec: timeout.subsec_nanos() as _,
+    });
+    let timeout = timeout.as_ref().map_or(core::ptr::null(), |timeout| timeout as *const libc::timespec);
+
+    // spurious wake ups, timeouts and interrupts are all handled by checking the count again
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            futex as *const AtomicU32,
+            libc::FUTEX_WAIT | libc::FUTEX_PRIVATE_FLAG,
+            expected,
+            timeout,
+        );
+    }
+}
+
+fn futex_wake(futex: &AtomicU32) {
+    unsafe {
+        libc::syscall(
+            libc::SYS_futex,
+            futex as *const AtomicU32,
+            libc::FUTEX_WAKE | libc::FUTEX_PRIVATE_FLAG,
+            i32::MAX,
+        );
+    }
+}
+
+impl FutexStrategy {
+    #[inline]
+    fn exit(&self, side: usize) {
+        if self.counts[side].fetch_sub(1, Ordering::SeqCst) == WAITING | 1 {
+            self.wake(side)
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn wake(&self, side: usize) {
+        self.counts[side].fetch_and(!WAITING, Ordering::SeqCst);
+        futex_wake(&self.counts[side]);
+
+        if self.waker.is_registered() {
+            self.waker.wake()
+        }
+    }
+
+    // returns the state of the side if there are still readers in it
+    fn mark_waiting(&self, side: usize) -> Option<u32> {
+        self.counts[side]
+            .fetch_update(Ordering::SeqCst, Ordering::SeqCst, |count| {
+                if count & COUNT == 0 {
+                    None
+                } else {
+                    Some(count | WAITING)
+                }
+            })
+            .ok()
+            .map(|count| count | WAITING)
+    }
+}
+
+unsafe impl Strategy for FutexStrategy {
+    type Whitch = AtomicUsize;
+    type ReaderTag = ReaderTag;
+    type WriterTag = WriterTag;
+    type RawGuard = RawGuard;
+
+    type FastCapture = ();
+    type CaptureError = core::convert::Infallible;
+    type Capture = Capture;
+
+    #[inline]
+    unsafe fn reader_tag(&self) -> Self::ReaderTag { ReaderTag(()) }
+
+    #[inline]
+    unsafe fn writer_tag(&self) -> Self::WriterTag { WriterTag(()) }
+
+    fn has_active_readers(&self, _: &mut Self::WriterTag) -> bool {
+        self.counts.iter().any(|count| count.load(Ordering::SeqCst) & COUNT != 0)
+    }
+
+    #[inline]
+    fn try_capture_readers(&self, _: &mut Self::WriterTag) -> Result<Self::FastCapture, Self::CaptureError> { Ok(()) }
+
+    #[inline]
+    fn finish_capture_readers(&self, _: &mut Self::WriterTag, (): Self::FastCapture) -> Self::Capture {
+        let side = self.side.flip();
+        self.counts[side].fetch_add(0, Ordering::SeqCst);
+        Capture(side)
+    }
+
+    #[inline]
+    fn readers_have_exited(&self, &mut Capture(side): &mut Self::Capture) -> bool {
+        self.counts[side].load(Ordering::SeqCst) & COUNT == 0
+    }
+
+    fn poll_readers_have_exited(&self, capture: &mut Self::Capture, ctx: &mut Context<'_>) -> Poll<()> {
+        if self.readers_have_exited(capture) {
+            return Poll::Ready(())
+        }
+
+        self.waker.register(ctx.waker());
+
+        match self.mark_waiting(capture.0) {
+            None => Poll::Ready(()),
+            Some(_) => Poll::Pending,
+        }
+    }
+
+    fn park_writer(&self, &mut Capture(side): &mut Self::Capture, timeout: Option<Duration>) {
+        if let Some(count) = self.mark_waiting(side) {
+            futex_wait(&self.counts[side], count, timeout)
+        }
+    }
+
+    fn begin_guard(&self, _: &mut Self::ReaderTag) -> Self::RawGuard {
+        RawGuard(self.side.enter(
+            (COUNT - 1) as usize,
+            |side| (self.counts[side].fetch_add(1, Ordering::SeqCst) & COUNT) as usize,
+            |side| self.exit(side),
+        ))
+    }
+
+    #[inline]
+    fn end_guard(&self, RawGuard(side): Self::RawGuard) { self.exit(side) }
+
+    #[cfg(feature = "metrics")]
+    fn captured_readers(&self, &Capture(side): &Self::Capture) -> usize {
+        (self.counts[side].load(Ordering::Relaxed) & COUNT) as usize
+    }
+}
@@ -1,9 +1,16 @@
-use crate::{sync::Capture as RawCapture, Strategy};
-use core::sync::atomic::AtomicBool;
-use crossbeam_utils::Backoff;
-use parking_lot::Condvar;
-
-pub type BufferData<B, E = ()> = crate::BufferData<AtomicBool, ParkStrategy, B, E>;
+use crate::{
+    diagnostics::ReaderInfo,
+    loom::{atomic::AtomicUsize, Condvar, Mutex},
+    sync::Capture as RawCapture,
+    Strategy,
+};
+use core::{
+    task::{Context, Poll},
+    time::Duration,
+};
+use std::borrow::Cow;
+
+pub type BufferData<B, E = ()> = crate::BufferData<AtomicUsize, ParkStrategy, B, E>;
 
 crate::__imp_make_newtype! {
     crate::sync::park::ParkStrategy, core::convert::Infallible, ArcInner, std::sync::Arc
@@ -12,13 +19,11 @@ crate::__imp_make_newtype! {
 #[derive(Default)]
 pub struct ParkStrategy {
     raw: super::SyncStrategy,
+    lock: Mutex<()>,
     cv: Condvar,
 }
 
-pub struct Capture {
-    raw: RawCapture,
-    backoff: Backoff,
-}
+pub struct Capture(RawCapture);
 
 pub struct ReaderTag(super::ReaderTag);
 pub struct WriterTag(super::WriterTag);
@@ -27,14 +32,27 @@ pub struct RawGuard(super::RawGuard);
 impl ParkStrategy {
     #[cold]
     #[inline(never)]
-    fn park(&self) {
-        self.cv
-            .wait_for(&mut self.raw.tag_list.lock(), std::time::Duration::from_micros(100));
+    fn park(&self, capture: &RawCapture, timeout: Option<Duration>) {
+        let mut lock = self.lock.lock();
+        let is_waiting = capture.mark_waiting();
+
+        match timeout {
+            _ if !is_waiting => (),
+            None => self.cv.wait(&mut lock),
+            Some(timeout) => drop(self.cv.wait_for(&mut lock, timeout)),
+        }
+    }
+
+    #[cold]
+    #[inline(never)]
+    fn unpark(&self) {
+        let _lock = self.lock.lock();
+        self.cv.notify_one();
     }
 }
 
 unsafe impl Strategy for ParkStrategy {
-    type Whitch = AtomicBool;
+    type Whitch = AtomicUsize;
     type ReaderTag = ReaderTag;
     type WriterTag = WriterTag;
     type RawGuard = RawGuard;
@@ -43,12 +61,17 @@ unsafe impl Strategy for ParkStrategy {
     type CaptureError = core::convert::Infallible;
     type Capture = Capture;
 
+    const READERS_EXIT_IN_END_GUARD: bool = true;
+
     #[inline]
     unsafe fn reader_tag(&self) -> Self::ReaderTag { ReaderTag(self.raw.reader_tag()) }
 
     #[inline]
     unsafe fn writer_tag(&self) -> Self::WriterTag { WriterTag(self.raw.writer_tag()) }
 
+    #[inline]
+    fn has_active_readers(&self, WriterTag(tag): &mut Self::WriterTag) -> bool { self.raw.has_active_readers(tag) }
+
     #[inline]
     fn try_capture_readers(
         &self,
@@ -59,37 +82,49 @@ unsafe impl Strategy for ParkStrategy {
 
     #[inline]
     fn finish_capture_readers(&self, WriterTag(tag): &mut Self::WriterTag, (): Self::FastCapture) -> Self::Capture {
-        Capture {
-            raw: self.raw.finish_capture_readers(tag, ()),
-            backoff: Backoff::new(),
-        }
+        Capture(self.raw.finish_capture_readers(tag, ()))
     }
 
     #[inline]
-    fn readers_have_exited(&self, capture: &mut Self::Capture) -> bool {
-        #[cold]
-        fn cold(strategy: &ParkStrategy, backoff: &Backoff) {
-            if backoff.is_completed() {
-                strategy.park();
-            } else {
-                backoff.snooze();
-            }
-        }
+    fn readers_have_exited(&self, Capture(capture): &mut Self::Capture) -> bool { self.raw.readers_have_exited(capture) }
+
+    #[inline]
+    fn poll_readers_have_exited(&self, Capture(capture): &mut Self::Capture, ctx: &mut Context<'_>) -> Poll<()> {
+        self.raw.poll_readers_have_exited(capture, ctx)
+    }
 
-        let is_completed = self.raw.readers_have_exited(&mut capture.raw);
+    #[inline]
+    fn park_writer(&self, Capture(capture): &mut Self::Capture, timeout: Option<Duration>) { self.park(capture, timeout) }
+
+    #[inline]
+    fn begin_guard(&self, ReaderTag(tag): &mut Self::ReaderTag) -> Self::RawGuard {
+        RawGuard(self.raw.begin_guard(tag))
+    }
 
-        if !is_completed {
-            cold(self, &capture.backoff)
+    #[inline]
+    fn end_guard(&self, RawGuard(guard): Self::RawGuard) {
+        if self.raw.release(guard) {
+            self.unpark()
         }
+    }
 
-        is_completed
+    #[inline]
+    fn describe_reader<F: FnOnce() -> ReaderInfo>(&self, ReaderTag(tag): &mut Self::ReaderTag, info: F) {
+        self.raw.describe_reader(tag, info)
     }
 
     #[inline]
-    fn begin_guard(&self, ReaderTag(tag): &mut Self::ReaderTag) -> Self::RawGuard {
-        RawGuard(self.raw.begin_guard(tag))
+    fn name_reader(&self, ReaderTag(tag): &mut Self::ReaderTag, name: Cow<'static, str>) { self.raw.name_reader(tag, name) }
+
+    #[inline]
+    fn active_readers(&self) -> Vec<ReaderInfo> { self.raw.active_readers() }
+
+    #[inline]
+    fn blocking_readers(&self, Capture(capture): &Self::Capture) -> Vec<ReaderInfo> {
+        self.raw.blocking_readers(capture)
     }
 
+    #[cfg(feature = "metrics")]
     #[inline]
-    fn end_guard(&self, RawGuard(guard): Self::RawGuard) { self.raw.end_guard(guard) }
+    fn captured_readers(&self, Capture(capture): &Self::Capture) -> usize { self.raw.captured_readers(capture) }
 }
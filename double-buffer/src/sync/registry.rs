@@ -0,0 +1,159 @@
+use super::Tag;
+use crate::{
+    loom::atomic::{AtomicPtr, AtomicUsize, Ordering},
+    thin::{Arc, ArcInner},
+};
+use core::{mem::ManuallyDrop, ptr};
+use std::boxed::Box;
+
+const CHUNK_SIZE: usize = 16;
+// the free list packs an index and an update counter into one `usize`, so it works on every
+// target with pointer sized atomics. On 32-bit targets that allows for 65520 readers
+const INDEX_BITS: u32 = usize::BITS / 2;
+const INDEX_MASK: usize = (1 << INDEX_BITS) - 1;
+// bucket `i` holds `CHUNK_SIZE << i` slots, which covers every index that fits in the free list
+const BUCKETS: usize = (INDEX_BITS - CHUNK_SIZE.trailing_zeros()) as usize;
+const MAX_TAGS: usize = CHUNK_SIZE * ((1 << BUCKETS) - 1);
+
+type Slot = AtomicPtr<ArcInner<Tag>>;
+
+// an append-only array of reader tags, stored in buckets that double in size
+//
+// slots are only ever filled, never emptied, so anyone may inspect a filled slot without
+// synchronizing with readers that are registering. The registry keeps a strong reference
+// to every tag, and the tags of dropped readers are kept on a free list for new readers.
+#[derive(Default)]
+pub struct Registry {
+    buckets: [AtomicPtr<Slot>; BUCKETS],
+    len: AtomicUsize,
+    // a Treiber stack of the indices of free tags. The low half holds the top index plus one, or zero
+    // if the stack is empty, and the high half counts the updates, so that a pop can't succeed
+    // after the stack was popped and pushed back to the same top in the meantime
+    free: AtomicUsize,
+}
+
+const fn bucket_len(bucket: usize) -> usize { CHUNK_SIZE << bucket }
+
+// the update counter of the free list, incremented
+const fn bump(free: usize) -> usize { ((free >> INDEX_BITS).wrapping_add(1)) << INDEX_BITS }
+
+// the bucket holding the slot and the index of the slot in that bucket
+fn locate(index: usize) -> (usize, usize) {
+    let chunk = index / CHUNK_SIZE + 1;
+    let bucket = (usize::BITS - 1 - chunk.leading_zeros()) as usize;
+    (bucket, index - CHUNK_SIZE * ((1 << bucket) - 1))
+}
+
+impl Registry {
+    fn bucket(&self, bucket: usize) -> Option<&[Slot]> {
+        let slots = self.buckets[bucket].load(Ordering::Acquire);
+
+        if slots.is_null() {
+            None
+        } else {
+            Some(unsafe { core::slice::from_raw_parts(slots, bucket_len(bucket)) })
+        }
+    }
+
+    #[cold]
+    fn alloc_bucket(&self, bucket: usize) -> &[Slot] {
+        let slots: Box<[Slot]> = (0..bucket_len(bucket)).map(|_| Slot::default()).collect();
+        let slots = Box::into_raw(slots) as *mut Slot;
+
+        match self.buckets[bucket].compare_exchange(ptr::null_mut(), slots, Ordering::AcqRel, Ordering::Acquire) {
+            Ok(_) => unsafe { core::slice::from_raw_parts(slots, bucket_len(bucket)) },
+            Err(existing) => unsafe {
+                drop(Box::from_raw(ptr::slice_from_raw_parts_mut(slots, bucket_len(bucket))));
+                core::slice::from_raw_parts(existing, bucket_len(bucket))
+            },
+        }
+    }
+
+    // only called with the index of a tag that is already in the registry
+    fn get(&self, index: usize) -> ManuallyDrop<Arc<Tag>> {
+        let (bucket, offset) = locate(index);
+        let slots = self.bucket(bucket).expect("Tried to get a tag that isn't in the registry");
+        ManuallyDrop::new(unsafe { Arc::from_raw(slots[offset].load(Ordering::Acquire)) })
+    }
+
+    pub fn insert(&self) -> Arc<Tag> {
+        let index = self.len.fetch_add(1, Ordering::Relaxed);
+        assert!(index < MAX_TAGS, "Tried to register too many readers!");
+
+        let (bucket, offset) = locate(index);
+        let slots = match self.bucket(bucket) {
+            Some(slots) => slots,
+            None => self.alloc_bucket(bucket),
+        };
+
+        let tag = Arc::new(Tag::new(index));
+        slots[offset].store(Arc::into_raw(tag.clone()), Ordering::Release);
+        tag
+    }
+
+    pub fn tags(&self) -> impl Iterator<Item = ManuallyDrop<Arc<Tag>>> + '_ {
+        (0..BUCKETS)
+            .filter_map(move |bucket| self.bucket(bucket))
+            .flat_map(|slots| slots.iter())
+            .map(|slot| slot.load(Ordering::Acquire))
+            .filter(|ptr| !ptr.is_null())
+            .map(|ptr| ManuallyDrop::new(unsafe { Arc::from_raw(ptr) }))
+    }
+
+    // reuse the tag of a dropped reader
+    pub fn claim(&self) -> Option<Arc<Tag>> {
+        let mut free = self.free.load(Ordering::Acquire);
+
+        loop {
+            let top = (free & INDEX_MASK).checked_sub(1)?;
+            let tag = self.get(top);
+            let next = tag.next_free.load(Ordering::Relaxed);
+            let updated = bump(free) | next;
+
+            match self
+                .free
+                .compare_exchange_weak(free, updated, Ordering::Acquire, Ordering::Acquire)
+            {
+                Ok(_) => return Some(Arc::clone(&tag)),
+                Err(current) => free = current,
+            }
+        }
+    }
+
+    // the tag stays in the registry, so it remains valid while it is on the free list
+    pub fn release(&self, tag: &Tag) {
+        let mut free = self.free.load(Ordering::Relaxed);
+
+        loop {
+            tag.next_free.store(free & INDEX_MASK, Ordering::Relaxed);
+            let updated = bump(free) | (tag.index + 1);
+
+            match self
+                .free
+                .compare_exchange_weak(free, updated, Ordering::Release, Ordering::Relaxed)
+            {
+                Ok(_) => return,
+                Err(current) => free = current,
+            }
+        }
+    }
+}
+
+impl Drop for Registry {
+    fn drop(&mut self) {
+        unsafe {
+            for tag in self.tags() {
+                drop(ManuallyDrop::into_inner(tag))
+            }
+
+            // loom's atomics don't support `get_mut`, so the buckets are read with plain loads
+            for (bucket, slots) in self.buckets.iter().enumerate() {
+                let slots = slots.load(Ordering::Relaxed);
+
+                if !slots.is_null() {
+                    drop(Box::from_raw(ptr::slice_from_raw_parts_mut(slots, bucket_len(bucket))))
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,211 @@
+// a conformance suite for `Strategy` implementations
+//
+// each check panics if the strategy misbehaves. The checks only need readers to drop
+// their guards, so strategies that also need readers to do something else between
+// reads (like `QsbrStrategy`) can't be checked with them.
+
+use crate::{raw::Writer, Count, Strategy};
+use core::{
+    sync::atomic::{AtomicBool, AtomicUsize, Ordering},
+    time::Duration,
+};
+use std::{
+    sync::{mpsc, Arc},
+    thread,
+};
+
+const READERS: usize = 4;
+const SWAPS: usize = 1000;
+const CLONES: usize = 100;
+
+// long enough that only a swap that never completes should run into it
+const TIMEOUT: Duration = Duration::from_secs(10);
+
+type BufferData<S, const N: usize> = crate::BufferData<<S as Strategy>::Whitch, S, Buffer, (), Count<N>>;
+
+// the values are written one at a time, so a reader that sees different values
+// observed the buffer while the writer was writing to it
+#[derive(Default)]
+struct Buffer {
+    readers: AtomicUsize,
+    values: [AtomicUsize; 8],
+}
+
+impl Buffer {
+    fn read(&self) {
+        self.readers.fetch_add(1, Ordering::SeqCst);
+
+        let first = self.values[0].load(Ordering::Relaxed);
+
+        for value in self.values.iter() {
+            assert_eq!(
+                value.load(Ordering::Relaxed),
+                first,
+                "a reader observed the buffer while the writer was writing to it"
+            );
+        }
+
+        self.readers.fetch_sub(1, Ordering::SeqCst);
+    }
+
+    fn write(&mut self, value: usize) {
+        assert_eq!(
+            self.readers.load(Ordering::SeqCst),
+            0,
+            "the writer got access to a buffer that is still being read"
+        );
+
+        for slot in self.values.iter() {
+            slot.store(value, Ordering::Relaxed)
+        }
+    }
+}
+
+fn swap_buffers<S: Strategy, const N: usize>(writer: &mut Writer<Arc<BufferData<S, N>>>) {
+    let swap = unsafe { Writer::start_buffer_swap(writer) };
+
+    if Writer::finish_swap_timeout(writer, swap, TIMEOUT).is_err() {
+        panic!("a swap didn't complete within {:?} of the readers dropping their guards", TIMEOUT)
+    }
+}
+
+// `N` is the number of buffers, which has to match the strategy's `BUFFER_COUNT` if it has one
+pub fn check<S, const N: usize>()
+where
+    S: Strategy + Default + Send + Sync,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    readers_never_see_writes::<S, N>();
+    swaps_complete_once_guards_drop::<S, N>();
+    reader_tags::<S, N>();
+}
+
+// readers keep reading while the writer writes and swaps as fast as it can
+pub fn readers_never_see_writes<S, const N: usize>()
+where
+    S: Strategy + Default + Send + Sync,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    let (reader, mut writer) = crate::new(Arc::new(BufferData::<S, N>::default()));
+    let done = AtomicBool::new(false);
+
+    thread::scope(|s| {
+        for _ in 0..READERS {
+            let mut reader = reader.try_clone().unwrap();
+            let done = &done;
+
+            s.spawn(move || {
+                while !done.load(Ordering::Relaxed) {
+                    reader.get().read();
+                }
+            });
+        }
+
+        for i in 1..=SWAPS {
+            writer.write(i);
+            swap_buffers(&mut writer);
+        }
+
+        done.store(true, Ordering::Relaxed);
+    });
+}
+
+// the writer must not get the buffer that a guard is reading, so a swap that would hand it out
+// has to wait for the guard, and complete once it is dropped. With more than two buffers the
+// writer may move into the other buffers in the meantime
+pub fn swaps_complete_once_guards_drop<S, const N: usize>()
+where
+    S: Strategy + Default + Send + Sync,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    let (mut reader, mut writer) = crate::new(Arc::new(BufferData::<S, N>::default()));
+    let (acquired_tx, acquired_rx) = mpsc::channel();
+    let (release_tx, release_rx) = mpsc::channel::<()>();
+
+    // the sender is moved into the scope, so a failing check still releases the reader
+    thread::scope(move |s| {
+        s.spawn(move || {
+            let guard = reader.get();
+            // the guard counts as reading the buffer until it is dropped
+            guard.readers.fetch_add(1, Ordering::SeqCst);
+            acquired_tx.send(()).unwrap();
+            let _ = release_rx.recv();
+            guard.readers.fetch_sub(1, Ordering::SeqCst);
+        });
+
+        acquired_rx.recv().unwrap();
+
+        let pending = (1..=N).find_map(|i| {
+            let swap = unsafe { Writer::start_buffer_swap(&mut writer) };
+
+            match Writer::try_finish_swap(&writer, swap) {
+                Ok(()) => {
+                    writer.write(i);
+                    None
+                }
+                Err(swap) => Some(swap),
+            }
+        });
+
+        release_tx.send(()).unwrap();
+
+        if let Some(swap) = pending {
+            if Writer::finish_swap_timeout(&writer, swap, TIMEOUT).is_err() {
+                panic!("a swap didn't complete within {:?} of the reader dropping its guard", TIMEOUT)
+            }
+        }
+    });
+}
+
+// readers are created and dropped while the writer is swapping, and dropped readers must not hold up swaps
+pub fn reader_tags<S, const N: usize>()
+where
+    S: Strategy + Default + Send + Sync,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    let (reader, mut writer) = crate::new(Arc::new(BufferData::<S, N>::default()));
+    let done = AtomicBool::new(false);
+
+    thread::scope(|s| {
+        for _ in 0..READERS {
+            let reader = reader.try_clone().unwrap();
+            let done = &done;
+
+            s.spawn(move || {
+                for _ in 0..CLONES {
+                    let mut reader = reader.try_clone().unwrap();
+                    reader.get().read();
+                }
+
+                while !done.load(Ordering::Relaxed) {
+                    let mut reader = reader.try_clone().unwrap();
+                    reader.get().read();
+                }
+            });
+        }
+
+        for i in 1..=SWAPS / 10 {
+            writer.write(i);
+            swap_buffers(&mut writer);
+        }
+
+        done.store(true, Ordering::Relaxed);
+    });
+
+    drop(reader);
+
+    let swap = unsafe { Writer::start_buffer_swap(&mut writer) };
+    assert!(
+        Writer::try_finish_swap(&writer, swap).is_ok(),
+        "a swap was held up by readers that were already dropped"
+    );
+
+    // readers may outlive the writer
+    let reader = Writer::reader(&writer);
+    drop(writer);
+    drop(reader);
+}
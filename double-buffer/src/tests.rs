@@ -1,13 +1,37 @@
 use test_crossbeam_channel::bounded;
 use test_crossbeam_utils::thread::scope;
 
-use std::sync::Arc;
+use std::{
+    future::Future,
+    pin::Pin,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    task::{Context, Wake, Waker},
+};
 
-use crate::{new, sync::BufferData, Writer};
+use crate::{new, raw::TrySwap, sync::BufferData, Strategy, Writer};
 
 #[cfg(feature = "std")]
 mod map;
 
+// runs a generic test with each strategy, in a module named after the test. The attributes in
+// brackets apply to every test, the ones in front of a strategy only to its own test
+macro_rules! strategy_tests {
+    ($test:ident $attrs:tt { $($(#[$cfg:meta])* $name:ident($($args:tt)*)),* $(,)? }) => {
+        mod $test {
+            $(strategy_tests!(@test $attrs $(#[$cfg])* $name(super::$test::<$($args)*>));)*
+        }
+    };
+    (@test [$(#[$attr:meta])*] $(#[$cfg:meta])* $name:ident($($call:tt)*)) => {
+        #[test]
+        $(#[$attr])*
+        $(#[$cfg])*
+        fn $name() { $($call)*() }
+    };
+}
+
 #[test]
 pub fn is_dangling() {
     let buffer_data = Arc::new(BufferData::new((), ()));
@@ -16,6 +40,24 @@ pub fn is_dangling() {
     assert!(r.is_dangling());
 }
 
+#[test]
+fn read_after_writer_drop() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    *w = 10;
+    Writer::swap_buffers(&mut w);
+    assert!(!r.is_dangling());
+    let guard = r.try_get().unwrap();
+    drop(w);
+
+    assert_eq!(*guard, 10);
+    drop(guard);
+    assert!(r.is_dangling());
+    assert!(r.try_get().is_err());
+    assert!(r.try_clone().is_err());
+}
+
 #[test]
 fn clone_read() {
     let buffer_data = Arc::new(BufferData::new((), ()));
@@ -24,6 +66,41 @@ fn clone_read() {
     r.try_clone().unwrap();
 }
 
+#[test]
+#[cfg_attr(miri, ignore)]
+fn register_while_swapping() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (r, mut w) = new(buffer_data);
+    let done = AtomicBool::new(false);
+
+    let _ = scope(|s| {
+        for _ in 0..4 {
+            let r = r.try_clone().unwrap();
+            let done = &done;
+            let _ = s.spawn(move |_| {
+                let mut readers = Vec::new();
+
+                while !done.load(Ordering::Relaxed) {
+                    readers.push(r.try_clone().unwrap());
+                    let _ = *readers.last_mut().unwrap().get();
+
+                    // drop some readers so their slots get reclaimed
+                    if readers.len() > 40 {
+                        readers.truncate(20);
+                    }
+                }
+            });
+        }
+
+        for i in 0..1000 {
+            *w = i;
+            Writer::swap_buffers(&mut w);
+        }
+
+        done.store(true, Ordering::Relaxed);
+    });
+}
+
 #[test]
 fn write_before_read() {
     let buffer_data = Arc::new(BufferData::new(0, 0));
@@ -39,16 +116,168 @@ fn write_before_read() {
 }
 
 #[test]
-#[ignore = "this test will block forever"]
 fn swap_while_read() {
     let buffer_data = Arc::new(BufferData::new(0, 0));
     let (mut r, mut w) = new(buffer_data);
 
+    let guard = r.get();
+
+    // the swap can't finish while the guard is alive
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+    let swap = Writer::finish_swap_timeout(&w, swap, std::time::Duration::from_millis(10)).unwrap_err();
+
+    drop(guard);
+    Writer::finish_swap_timeout(&w, swap, std::time::Duration::from_secs(60)).unwrap();
+}
+
+#[test]
+#[cfg(feature = "detect-deadlock")]
+#[should_panic(expected = "this would deadlock")]
+fn detect_swap_while_read() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
     let _guard = r.get();
 
     Writer::swap_buffers(&mut w);
 }
 
+#[test]
+#[cfg(feature = "detect-deadlock")]
+#[cfg_attr(miri, ignore)]
+fn detect_deadlock_per_buffer() {
+    use crate::triple;
+
+    let (mut r, mut w) = new(Arc::new(triple::BufferData::from_buffers([0, 0, 0])));
+    let mut pinned = Writer::reader(&w);
+    let (send, recv) = bounded(0);
+
+    for _ in 0..3 {
+        Writer::swap_buffers(&mut w);
+    }
+
+    let _ = scope(|s| {
+        let _ = s.spawn(move |_| {
+            let _old = pinned.get_version(2).unwrap();
+            send.send(()).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        });
+
+        recv.recv().unwrap();
+
+        // the swap waits on the buffer holding version 2, not on the latest buffer that this guard is reading
+        let _guard = r.get();
+        Writer::swap_buffers(&mut w);
+    });
+}
+
+#[test]
+fn try_swap_while_read() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    let guard = r.get();
+
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+    let swap = Writer::try_finish_swap(&w, swap).unwrap_err();
+    let swap = Writer::finish_swap_timeout(&w, swap, std::time::Duration::from_millis(1)).unwrap_err();
+
+    drop(guard);
+
+    Writer::try_finish_swap(&w, swap).unwrap();
+}
+
+#[test]
+fn blocking_readers() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+    let mut idle = r.try_clone().unwrap();
+    idle.set_name("idle");
+    r.set_name("busy");
+
+    let _ = *idle.get();
+    let guard = r.get();
+
+    let active = Writer::active_readers(&w);
+    assert_eq!(active.len(), 1);
+    assert_eq!(active[0].name(), Some("busy"));
+    assert_eq!(active[0].location().file(), file!());
+
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+    let blocking = Writer::blocking_readers(&w, &swap);
+    assert_eq!(blocking.len(), 1);
+    assert_eq!(blocking[0].name(), Some("busy"));
+
+    drop(guard);
+
+    assert!(Writer::blocking_readers(&w, &swap).is_empty());
+    Writer::try_finish_swap(&w, swap).unwrap();
+}
+
+#[test]
+#[cfg(feature = "metrics")]
+fn swap_stats() {
+    use std::sync::atomic::AtomicUsize;
+
+    #[derive(Default)]
+    struct Hook(AtomicUsize);
+
+    impl crate::metrics::MetricsHook for Arc<Hook> {
+        fn swap_started(&self, _: usize) { self.0.fetch_add(1, Ordering::Relaxed); }
+    }
+
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+    let hook = Arc::new(Hook::default());
+    assert!(Writer::set_metrics_hook(&w, hook.clone()).is_ok());
+    assert!(Writer::set_metrics_hook(&w, hook.clone()).is_err());
+
+    Writer::swap_buffers(&mut w);
+
+    let guard = r.get();
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+    drop(guard);
+    Writer::finish_swap(&w, swap);
+
+    let stats = Writer::stats(&w);
+    assert_eq!(stats.swaps, 2);
+    assert_eq!(stats.finished_swaps, 2);
+    assert_eq!(stats.captured_readers, 1);
+    assert_eq!(stats.guards, 1);
+    assert!(stats.max_wait_time <= stats.wait_time);
+    assert_eq!(hook.0.load(Ordering::Relaxed), 2);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn watchdog() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+    let reported = Arc::new(AtomicBool::new(false));
+    let report = reported.clone();
+    r.set_name("slow");
+    Writer::set_watchdog(&mut w, std::time::Duration::from_millis(1), move |readers| {
+        assert_eq!(readers.len(), 1);
+        assert_eq!(readers[0].name(), Some("slow"));
+        report.store(true, Ordering::Relaxed);
+    });
+
+    let (tx, rx) = bounded(0);
+
+    let _ = scope(|s| {
+        let _ = s.spawn(move |_| {
+            let _guard = r.get();
+            tx.send(()).unwrap();
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        });
+
+        rx.recv().unwrap();
+        Writer::swap_buffers(&mut w);
+    });
+
+    assert!(reported.load(Ordering::Relaxed));
+}
+
 #[test]
 #[cfg_attr(miri, ignore)]
 fn wait() {
@@ -77,8 +306,114 @@ fn wait() {
     });
 }
 
+fn try_swap_buffers<S: Strategy + Default>() {
+    let mut buffer_data = crate::BufferData::<S::Whitch, S, i32, ()>::new(0, 0);
+    let (mut r, mut w) = buffer_data.split_mut();
+
+    *w = 10;
+    let guard = r.get();
+    assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::NotSwapped);
+    assert_eq!(*guard, 0);
+    drop(guard);
+
+    assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::Completed);
+    assert_eq!(*r.get(), 10);
+}
+
+strategy_tests!(try_swap_buffers [] {
+    local(crate::local::LocalStrategy),
+    sync(crate::sync::SyncStrategy),
+    #[cfg(feature = "std")]
+    park(crate::sync::park::ParkStrategy),
+    #[cfg(all(feature = "std", target_os = "linux"))]
+    futex(crate::sync::futex::FutexStrategy),
+    #[cfg(feature = "std")]
+    park_atomic(crate::park::Park<crate::atomic::AtomicStrategy>),
+    atomic(crate::atomic::AtomicStrategy),
+    sharded(crate::atomic::ShardedStrategy),
+});
+
 #[test]
-#[ignore = "this test will block forever"]
+fn try_swap_buffers_pending() {
+    let mut buffer_data = crate::sync::BufferData::<i32>::new(0, 0);
+    let (mut r, mut w) = buffer_data.split_mut();
+
+    let guard = r.get();
+    drop(Writer::begin_swap(&mut w));
+
+    // the pending swap can't complete yet, so the buffers can't be swapped again
+    assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::NotSwapped);
+    assert!(matches!(
+        unsafe { Writer::try_start_buffer_swap(&mut w) },
+        Err(crate::raw::StartSwapError::Pending)
+    ));
+    let pending = Writer::pending_swap(&mut w).unwrap().try_wait().err().unwrap();
+    drop(guard);
+    *pending.try_wait().ok().unwrap() = 10;
+
+    assert!(Writer::pending_swap(&mut w).is_none());
+    assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::Completed);
+    assert_eq!(*r.get(), 10);
+}
+
+fn finish_swap_timeout<S>()
+where
+    S: Strategy + Default + Send + Sync,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    use std::time::Duration;
+
+    let mut buffer_data = crate::BufferData::<S::Whitch, S, i32, ()>::new(0, 0);
+    let (mut r, mut w) = buffer_data.split_mut();
+
+    let (tx0, rx0) = bounded(1);
+    let (tx1, rx1) = bounded(1);
+
+    let _ = scope(|s| {
+        let r = &mut r;
+
+        let _ = s.spawn(move |_| {
+            let guard = r.get();
+            let _ = tx0.send(());
+            let _ = rx1.recv();
+            drop(guard);
+        });
+
+        let _ = rx0.recv();
+
+        let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+        let swap = Writer::finish_swap_timeout(&w, swap, Duration::from_millis(1)).unwrap_err();
+        let _ = tx1.send(());
+        Writer::finish_swap_timeout(&w, swap, Duration::from_secs(60)).unwrap();
+    });
+}
+
+strategy_tests!(finish_swap_timeout [#[cfg_attr(miri, ignore)]] {
+    sync(crate::sync::SyncStrategy),
+    #[cfg(feature = "std")]
+    park(crate::sync::park::ParkStrategy),
+    #[cfg(all(feature = "std", target_os = "linux"))]
+    futex(crate::sync::futex::FutexStrategy),
+    #[cfg(feature = "std")]
+    park_atomic(crate::park::Park<crate::atomic::AtomicStrategy>),
+    atomic(crate::atomic::AtomicStrategy),
+    sharded(crate::atomic::ShardedStrategy),
+});
+
+#[test]
+fn finish_swap_timeout_local() {
+    let buffer_data = std::rc::Rc::new(crate::local::BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    *w = 10;
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+    Writer::finish_swap_timeout(&w, swap, std::time::Duration::from_millis(1)).unwrap();
+    assert_eq!(*r.get(), 10);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
 fn blocks() {
     let buffer_data = Arc::new(BufferData::new(0, 0));
     let (mut r, mut w) = new(buffer_data);
@@ -94,8 +429,550 @@ fn blocks() {
             let _ = tx1.send(x.get());
         });
 
-        let _y = rx1.recv().unwrap();
+        let guard = rx1.recv().unwrap();
+
+        // a guard that was taken on another thread holds up the swap just the same
+        let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+        let swap = Writer::finish_swap_timeout(&w, swap, std::time::Duration::from_millis(10)).unwrap_err();
+
+        drop(guard);
+        Writer::try_finish_swap(&w, swap).unwrap();
+    });
+}
+
+struct WakeFlag(AtomicBool);
+
+impl Wake for WakeFlag {
+    fn wake(self: Arc<Self>) { self.0.store(true, Ordering::SeqCst) }
+}
+
+fn swap_async<S: Strategy + Default>() {
+    let mut buffer_data = crate::BufferData::<S::Whitch, S, i32, ()>::new(0, 0);
+    let (mut r, mut w) = buffer_data.split_mut();
+
+    let flag = Arc::new(WakeFlag(AtomicBool::new(false)));
+    let waker = Waker::from(flag.clone());
+    let mut ctx = Context::from_waker(&waker);
+
+    *w = 10;
+    let guard = r.get();
+
+    let mut swap = Writer::swap_buffers_async(&mut w);
+    assert!(Pin::new(&mut swap).poll(&mut ctx).is_pending());
+    assert!(!flag.0.load(Ordering::SeqCst));
+
+    drop(guard);
+
+    assert!(flag.0.load(Ordering::SeqCst));
+    assert!(Pin::new(&mut swap).poll(&mut ctx).is_ready());
+    assert_eq!(*r.get(), 10);
+}
+
+strategy_tests!(swap_async [] {
+    sync(crate::sync::SyncStrategy),
+    #[cfg(feature = "std")]
+    park(crate::sync::park::ParkStrategy),
+    #[cfg(all(feature = "std", target_os = "linux"))]
+    futex(crate::sync::futex::FutexStrategy),
+    #[cfg(feature = "std")]
+    park_atomic(crate::park::Park<crate::atomic::AtomicStrategy>),
+    atomic(crate::atomic::AtomicStrategy),
+    sharded(crate::atomic::ShardedStrategy),
+});
+
+#[test]
+fn swap_async_wakes_on_captured_readers() {
+    let mut buffer_data = crate::sync::BufferData::<i32>::new(0, 0);
+    let (mut r, mut w) = buffer_data.split_mut();
+    let mut other = r.clone();
+
+    let flag = Arc::new(WakeFlag(AtomicBool::new(false)));
+    let waker = Waker::from(flag.clone());
+    let mut ctx = Context::from_waker(&waker);
+
+    let guard = r.get();
+
+    let mut swap = Writer::swap_buffers_async(&mut w);
+    assert!(Pin::new(&mut swap).poll(&mut ctx).is_pending());
+
+    // readers that the swap isn't waiting on don't wake it
+    drop(other.get());
+    assert!(!flag.0.load(Ordering::SeqCst));
+
+    drop(guard);
+    assert!(flag.0.load(Ordering::SeqCst));
+    assert!(Pin::new(&mut swap).poll(&mut ctx).is_ready());
+}
+
+#[test]
+fn sharded_readers() {
+    let mut buffer_data = crate::atomic::sharded::BufferData::new(0, 0);
+    let (mut r0, mut w) = buffer_data.split_mut();
+    let mut r1 = r0.try_clone().unwrap();
+
+    *w = 10;
+    let guard = r0.get();
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+
+    // a reader on another shard doesn't hold up the swap
+    let swap = Writer::try_finish_swap(&w, swap).unwrap_err();
+    assert_eq!(*r1.get(), 10);
+    let swap = Writer::try_finish_swap(&w, swap).unwrap_err();
+
+    assert_eq!(*guard, 0);
+    drop(guard);
+    Writer::try_finish_swap(&w, swap).unwrap();
+
+    // readers are assigned shards round robin, so this one shares a shard with `r0`, but
+    // it started after the swap so it doesn't hold it up
+    let mut readers: std::vec::Vec<_> = (0..15).map(|_| r0.try_clone().unwrap()).collect();
+    let r16 = readers.last_mut().unwrap();
+    let guard = r0.get();
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+    let late = r16.get();
+    let swap = Writer::try_finish_swap(&w, swap).unwrap_err();
+
+    drop(guard);
+    Writer::try_finish_swap(&w, swap).unwrap();
+    assert_eq!(*late, 0);
+}
+
+fn swap_under_load<S>()
+where
+    S: Strategy + Default + Send + Sync,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    let mut buffer_data = crate::BufferData::<S::Whitch, S, i32, ()>::new(0, 0);
+    let (r, mut w) = buffer_data.split_mut();
+    let done = AtomicBool::new(false);
+
+    let _ = scope(|s| {
+        for _ in 0..4 {
+            let mut r = r.try_clone().unwrap();
+            let done = &done;
+            let _ = s.spawn(move |_| {
+                while !done.load(Ordering::Relaxed) {
+                    let _ = *r.get();
+                }
+            });
+        }
+
+        // readers that start on the new buffer must not hold up the swap
+        for i in 0..1000 {
+            *w = i;
+            Writer::swap_buffers(&mut w);
+        }
+
+        done.store(true, Ordering::Relaxed);
+    });
+}
+
+strategy_tests!(swap_under_load [#[cfg_attr(miri, ignore)]] {
+    #[cfg(all(feature = "std", target_os = "linux"))]
+    futex(crate::sync::futex::FutexStrategy),
+    atomic(crate::atomic::AtomicStrategy),
+});
+
+#[cfg(feature = "testing")]
+fn conformance<S, const N: usize>()
+where
+    S: Strategy + Default + Send + Sync,
+    S::Whitch: Send + Sync,
+    S::ReaderTag: Send,
+{
+    crate::testing::check::<S, N>()
+}
+
+strategy_tests!(conformance [#[cfg_attr(miri, ignore)] #[cfg(feature = "testing")]] {
+    sync(crate::sync::SyncStrategy, 2),
+    park(crate::sync::park::ParkStrategy, 2),
+    #[cfg(target_os = "linux")]
+    futex(crate::sync::futex::FutexStrategy, 2),
+    park_atomic(crate::park::Park<crate::atomic::AtomicStrategy>, 2),
+    atomic(crate::atomic::AtomicStrategy, 2),
+    sharded(crate::atomic::ShardedStrategy, 2),
+    triple(crate::triple::TripleStrategy, 3),
+    ring(crate::ring::RingStrategy<5>, 5),
+});
+
+#[test]
+fn wait_policy() {
+    use crate::wait::{Parker, Spin};
+
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    *w = 10;
+    Writer::swap_buffers_with_policy(&mut w, Spin);
+    assert_eq!(*r.get(), 10);
+
+    let mut guard = Some(r.get());
+    let mut waits = 0;
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+    Writer::finish_swap_with_policy(&w, swap, |_: Parker<'_>| {
+        waits += 1;
+
+        if waits == 3 {
+            guard = None;
+        }
+    });
+    assert_eq!(waits, 3);
+}
+
+#[test]
+fn drop_pending_swap() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    let flag = Arc::new(WakeFlag(AtomicBool::new(false)));
+    let waker = Waker::from(flag);
+    let mut ctx = Context::from_waker(&waker);
+
+    *w = 10;
+    let guard = r.get();
+
+    let mut swap = Writer::swap_buffers_async(&mut w);
+    assert!(Pin::new(&mut swap).poll(&mut ctx).is_pending());
+    drop(swap);
+    drop(guard);
+
+    *w = 20;
+    assert_eq!(*r.get(), 10);
+    assert_eq!(*w, 20);
+}
+
+#[test]
+fn swap_async_after_pending_swap() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    let flag = Arc::new(WakeFlag(AtomicBool::new(false)));
+    let waker = Waker::from(flag);
+    let mut ctx = Context::from_waker(&waker);
+
+    *w = 10;
+    let guard = r.get();
+
+    let mut swap = Writer::swap_buffers_async(&mut w);
+    assert!(Pin::new(&mut swap).poll(&mut ctx).is_pending());
+    drop(swap);
+
+    // the second swap waits for the first one before swapping the buffers
+    let mut swap = Writer::swap_buffers_async(&mut w);
+    assert!(Pin::new(&mut swap).poll(&mut ctx).is_pending());
+    drop(guard);
+    assert!(Pin::new(&mut swap).poll(&mut ctx).is_ready());
+
+    assert_eq!(*r.get(), 0);
+    assert_eq!(*w, 10);
+}
+
+#[test]
+fn swap_and_sync() {
+    let buffer_data = Arc::new(BufferData::new(std::vec![], std::vec![]));
+    let (mut r, mut w) = new(buffer_data);
+
+    w.push(1);
+    Writer::swap_and_sync(&mut w);
+    assert_eq!(*r.get(), [1]);
+    assert_eq!(*w, [1]);
+
+    w.push(2);
+    Writer::swap_and_sync_with(&mut w, |published, write| write.push(published[1]));
+    assert_eq!(*r.get(), [1, 2]);
+    assert_eq!(*w, [1, 2]);
+}
+
+#[test]
+fn begin_swap() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    *w = 10;
+    let guard = r.get();
+
+    let swap = Writer::begin_swap(&mut w);
+    assert_eq!(*swap.read(), 10);
+    let swap = swap.try_wait().unwrap_err();
+    drop(guard);
+
+    let write = swap.try_wait().ok().unwrap();
+    assert_eq!(*write, 0);
+    *write = 20;
+
+    drop(Writer::begin_swap(&mut w));
+    assert_eq!(*r.get(), 20);
+    assert_eq!(*w, 10);
+}
+
+#[test]
+fn wait_for_swap_timeout() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (r, _w) = new(buffer_data);
+
+    assert!(!r.wait_for_swap_timeout(std::time::Duration::from_millis(1)).unwrap());
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn wait_for_swap() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    let done = AtomicBool::new(false);
+
+    let _ = scope(|s| {
+        let w = &mut w;
+        let done = &done;
+
+        let _ = s.spawn(move |_| {
+            while !done.load(Ordering::Relaxed) {
+                **w = 10;
+                Writer::swap_buffers(w);
+            }
+        });
+
+        r.wait_for_swap().unwrap();
+        assert_eq!(*r.get(), 10);
+        done.store(true, Ordering::Relaxed);
+    });
+
+    drop(w);
+    assert!(r.wait_for_swap().is_err());
+}
+
+#[test]
+fn changed() {
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    let flag = Arc::new(WakeFlag(AtomicBool::new(false)));
+    let waker = Waker::from(flag.clone());
+    let mut ctx = Context::from_waker(&waker);
+
+    let mut changed = r.changed();
+    assert!(Pin::new(&mut changed).poll(&mut ctx).is_pending());
+
+    Writer::swap_buffers(&mut w);
+
+    assert!(flag.0.load(Ordering::SeqCst));
+    assert!(Pin::new(&mut changed).poll(&mut ctx).is_ready());
+
+    // the swap happened after the last read, so it isn't missed by a later call
+    let mut changed = r.changed();
+    assert!(Pin::new(&mut changed).poll(&mut ctx).is_ready());
+    assert!(r.wait_for_swap_timeout(std::time::Duration::from_millis(1)).unwrap());
+
+    let _ = r.get();
+    let mut changed = r.changed();
+    assert!(Pin::new(&mut changed).poll(&mut ctx).is_pending());
+    drop(w);
+    assert!(matches!(Pin::new(&mut changed).poll(&mut ctx), std::task::Poll::Ready(Err(_))));
+}
+
+#[test]
+fn versions() {
+    use crate::raw::ReaderGuard;
+
+    let buffer_data = Arc::new(BufferData::new(0, 0));
+    let (mut r, mut w) = new(buffer_data);
+
+    let version = ReaderGuard::version(&r.get());
+    assert_eq!(version, Writer::version(&w));
+    assert!(r.get_if_newer(version).is_none());
+
+    *w = 10;
+    Writer::swap_buffers(&mut w);
+
+    let guard = r.get_if_newer(version).unwrap();
+    assert_eq!(*guard, 10);
+    assert_eq!(ReaderGuard::version(&guard), version + 1);
+    assert_eq!(ReaderGuard::version(&guard), Writer::version(&w));
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn park_timeout() {
+    use crate::{park::Park, qsbr::QsbrStrategy, raw::BufferDataBuilder};
+
+    // qsbr readers exit without going through `end_guard`, so the parked writer has to check on them
+    let mut buffer_data = BufferDataBuilder {
+        buffers: [0, 0],
+        strategy: Park::with_timeout(QsbrStrategy::default(), Some(std::time::Duration::from_micros(100))),
+        extra: (),
+    }
+    .build::<std::sync::atomic::AtomicUsize>();
+    let (r, mut w) = buffer_data.split_mut();
+
+    let _ = scope(|s| {
+        let _ = s.spawn(move |_| {
+            std::thread::sleep(std::time::Duration::from_millis(20));
+            drop(r)
+        });
 
         Writer::swap_buffers(&mut w);
     });
 }
+
+#[test]
+fn qsbr() {
+    let mut buffer_data = crate::qsbr::BufferData::new(0, 0);
+    let (mut r0, mut w) = buffer_data.split_mut();
+    let mut r1 = r0.try_clone().unwrap();
+
+    *w = 10;
+    assert_eq!(*r0.get(), 0);
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+
+    // guards are free, so the writer waits for every reader to announce a quiescent state
+    let swap = Writer::try_finish_swap(&w, swap).unwrap_err();
+    r0.quiescent();
+    let swap = Writer::try_finish_swap(&w, swap).unwrap_err();
+    drop(r1.get());
+    let swap = Writer::try_finish_swap(&w, swap).unwrap_err();
+    r1.quiescent();
+    Writer::try_finish_swap(&w, swap).unwrap();
+    assert_eq!(*r0.get(), 10);
+
+    // dropped readers never hold up a swap
+    drop(r1);
+    let swap = unsafe { Writer::start_buffer_swap(&mut w) };
+    let swap = Writer::try_finish_swap(&w, swap).unwrap_err();
+    r0.quiescent();
+    Writer::try_finish_swap(&w, swap).unwrap();
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn triple_buffer() {
+    use crate::triple;
+
+    let (mut r, mut w) = new(Arc::new(triple::BufferData::from_buffers([0, 0, 0])));
+    let mut r1 = Writer::reader(&w);
+    let (send, recv) = bounded(0);
+
+    // a reader holding on to a buffer never makes the writer wait, no matter which buffer it holds
+    let first = r1.get();
+
+    let _ = scope(|s| {
+        let w = &mut w;
+        let _ = s.spawn(move |_| {
+            for i in 1..=10 {
+                **w = i * 10;
+                Writer::swap_buffers(w);
+            }
+
+            send.send(()).unwrap();
+        });
+
+        let swapped = recv.recv_timeout(std::time::Duration::from_secs(10));
+        assert_eq!(*first, 0);
+        drop(first);
+        swapped.expect("the writer waited on a reader");
+    });
+
+    assert_eq!(*r.get(), 100);
+    assert_eq!(*Writer::read(&w), 100);
+
+    // the writer only has to wait once readers hold both buffers other than the latest one
+    let first = r1.get();
+    *w = 110;
+    Writer::swap_buffers(&mut w);
+    let mut r2 = Writer::reader(&w);
+    let second = r2.get();
+    *w = 120;
+    assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::NotSwapped);
+    assert_eq!(*r.get(), 110);
+
+    drop(first);
+    assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::Completed);
+    assert_eq!(*r.get(), 120);
+    assert_eq!(*second, 110);
+}
+
+#[test]
+#[cfg_attr(miri, ignore)]
+fn triple_buffer_threaded() {
+    use crate::triple;
+
+    let (_, mut w) = new(Arc::new(triple::BufferData::<[u64; 4]>::default()));
+    let done = AtomicBool::new(false);
+
+    let _ = scope(|s| {
+        for _ in 0..4 {
+            let mut r = Writer::reader(&w);
+            let done = &done;
+
+            let _ = s.spawn(move |_| {
+                let mut last = 0;
+
+                while !done.load(Ordering::Relaxed) {
+                    let guard = r.get();
+                    assert!(guard.iter().all(|&x| x == guard[0]));
+                    assert!(guard[0] >= last);
+                    last = guard[0];
+                }
+            });
+        }
+
+        for i in 1..10_000 {
+            *w = [i; 4];
+            Writer::swap_buffers(&mut w);
+        }
+
+        done.store(true, Ordering::Relaxed);
+    });
+}
+
+#[test]
+fn ring_history() {
+    use crate::{raw::ReaderGuard, ring};
+
+    let (mut r, mut w) = new(Arc::new(ring::BufferData::<_, 4>::from_buffers([0; 4])));
+    let mut pinned = Writer::reader(&w);
+
+    *w = 10;
+    Writer::swap_buffers(&mut w);
+
+    let first = pinned.get();
+    let version = ReaderGuard::version(&first);
+    assert_eq!(version, Writer::version(&w));
+
+    // the writer skips the pinned buffer and keeps rotating through the others
+    for i in 2..=10 {
+        *w = i * 10;
+        assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::Completed);
+        assert_eq!(*r.get(), i * 10);
+    }
+
+    // the pinned version and the one before the latest stay readable, the others were overwritten
+    let latest = Writer::version(&w);
+    assert_eq!(*first, 10);
+    assert_eq!(r.get_version(version).as_deref(), Some(&10));
+    assert_eq!(r.get_version(latest - 1).as_deref(), Some(&90));
+    assert!(r.get_version(version + 1).is_none());
+
+    // once the pinned buffer is the oldest free one, the writer claims it again
+    drop(first);
+    *w = 110;
+    Writer::swap_buffers(&mut w);
+    assert_eq!(*r.get(), 110);
+    assert!(r.get_version(version).is_none());
+
+    // the writer only has to wait once readers hold every buffer except the latest one
+    let (mut second, mut third) = (Writer::reader(&w), Writer::reader(&w));
+    let previous = pinned.get_version(Writer::version(&w) - 1).unwrap();
+    let latest = second.get();
+    *w = 120;
+    assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::Completed);
+    let newest = third.get();
+    *w = 130;
+    assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::NotSwapped);
+    assert_eq!((*previous, *latest, *newest), (100, 110, 120));
+
+    drop(previous);
+    assert_eq!(Writer::try_swap_buffers(&mut w), TrySwap::Completed);
+    assert_eq!(*r.get(), 130);
+    assert_eq!(*latest, 110);
+}
@@ -1,12 +1,15 @@
-use crate::TrustedRadium;
-use core::{convert::TryFrom, mem::ManuallyDrop, pin::Pin, sync::atomic::Ordering};
+use crate::{
+    loom::atomic::{self, AtomicUsize, Ordering},
+    TrustedRadium,
+};
+use core::{convert::TryFrom, mem::ManuallyDrop, pin::Pin};
 use std::boxed::Box;
 
 pub type Rc<T> = Thin<T, core::cell::Cell<usize>>;
-pub type Arc<T> = Thin<T, core::sync::atomic::AtomicUsize>;
+pub type Arc<T> = Thin<T, AtomicUsize>;
 
 pub type RcInner<T> = ThinInner<T, core::cell::Cell<usize>>;
-pub type ArcInner<T> = ThinInner<T, core::sync::atomic::AtomicUsize>;
+pub type ArcInner<T> = ThinInner<T, AtomicUsize>;
 
 pub struct Thin<T: ?Sized, S: TrustedRadium<Item = usize>> {
     ptr: *mut ThinInner<T, S>,
@@ -80,6 +83,13 @@ impl<T, S: TrustedRadium<Item = usize>> Thin<T, S> {
 impl<T: ?Sized, S: TrustedRadium<Item = usize>> Thin<T, S> {
     pub fn strong_count(this: &Self) -> usize { unsafe { (*this.ptr).strong.load(Ordering::Acquire) } }
 
+    pub fn into_raw(this: Self) -> *mut ThinInner<T, S> { ManuallyDrop::new(this).ptr }
+
+    /// # Safety
+    ///
+    /// `ptr` must come from `Thin::into_raw`, and may only be turned back into a `Thin` once.
+    pub unsafe fn from_raw(ptr: *mut ThinInner<T, S>) -> Self { Self { ptr } }
+
     fn strong(&self) -> &S { unsafe { &(*self.ptr).strong } }
 }
 
@@ -126,7 +136,7 @@ impl<T: ?Sized, S: TrustedRadium<Item = usize>> Drop for Thin<T, S> {
         unsafe {
             if 1 == self.strong().fetch_sub(1, Ordering::Release) {
                 if !S::IS_LOCAL {
-                    core::sync::atomic::fence(Ordering::Acquire);
+                    atomic::fence(Ordering::Acquire);
                 }
                 drop_slow(Box::from_raw(self.ptr))
             }
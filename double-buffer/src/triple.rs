@@ -0,0 +1,9 @@
+use crate::{loom::atomic::AtomicUsize, ring::RingStrategy, Count};
+
+pub type TripleStrategy = RingStrategy<3>;
+
+pub type BufferData<B, E = ()> = crate::BufferData<AtomicUsize, TripleStrategy, B, E, Count<3>>;
+
+crate::__imp_make_newtype! {
+    crate::triple::TripleStrategy, core::convert::Infallible, ArcInner, std::sync::Arc
+}
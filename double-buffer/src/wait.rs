@@ -0,0 +1,68 @@
+use core::time::Duration;
+use crossbeam_utils::Backoff;
+
+pub trait WaitPolicy {
+    // called every time the writer finds readers still reading the buffer it is waiting for
+    fn wait(&mut self, parker: Parker<'_>);
+}
+
+pub struct Parker<'a> {
+    park: &'a mut dyn FnMut(Option<Duration>),
+    timeout: Option<Duration>,
+}
+
+impl<'a> Parker<'a> {
+    pub(crate) fn new(park: &'a mut dyn FnMut(Option<Duration>), timeout: Option<Duration>) -> Self {
+        Self { park, timeout }
+    }
+
+    // the time left before the writer gives up on the swap, if it is waiting with a timeout
+    pub fn timeout(&self) -> Option<Duration> { self.timeout }
+
+    // block until a reader may have exited, if the strategy supports blocking
+    pub fn park(self) { (self.park)(self.timeout) }
+
+    pub fn park_timeout(self, timeout: Duration) {
+        let timeout = self.timeout.map_or(timeout, |left| left.min(timeout));
+        (self.park)(Some(timeout))
+    }
+}
+
+impl<F: FnMut(Parker<'_>)> WaitPolicy for F {
+    fn wait(&mut self, parker: Parker<'_>) { self(parker) }
+}
+
+#[derive(Default, Clone, Copy)]
+pub struct Spin;
+
+impl WaitPolicy for Spin {
+    fn wait(&mut self, _: Parker<'_>) { core::hint::spin_loop() }
+}
+
+#[cfg(feature = "std")]
+#[derive(Default)]
+pub struct SpinThenYield(Backoff);
+
+#[cfg(feature = "std")]
+impl WaitPolicy for SpinThenYield {
+    fn wait(&mut self, _: Parker<'_>) {
+        if self.0.is_completed() {
+            std::thread::yield_now()
+        } else {
+            self.0.snooze()
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SpinThenPark(Backoff);
+
+impl WaitPolicy for SpinThenPark {
+    fn wait(&mut self, parker: Parker<'_>) {
+        if self.0.is_completed() {
+            parker.park()
+        }
+
+        self.0.snooze()
+    }
+}
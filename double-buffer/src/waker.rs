@@ -0,0 +1,39 @@
+use crate::loom::{
+    atomic::{AtomicBool, Ordering},
+    Mutex,
+};
+use core::task::Waker;
+
+#[derive(Default)]
+pub(crate) struct AtomicWaker {
+    is_registered: AtomicBool,
+    waker: Mutex<Option<Waker>>,
+}
+
+impl AtomicWaker {
+    pub fn register(&self, waker: &Waker) {
+        let mut slot = self.waker.lock();
+
+        match *slot {
+            Some(ref old) if old.will_wake(waker) => (),
+            _ => *slot = Some(waker.clone()),
+        }
+
+        self.is_registered.store(true, Ordering::SeqCst);
+    }
+
+    #[inline]
+    pub fn is_registered(&self) -> bool { self.is_registered.load(Ordering::SeqCst) }
+
+    #[cold]
+    #[inline(never)]
+    pub fn wake(&self) {
+        if self.is_registered.swap(false, Ordering::SeqCst) {
+            let waker = self.waker.lock().take();
+
+            if let Some(waker) = waker {
+                waker.wake()
+            }
+        }
+    }
+}